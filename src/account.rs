@@ -1,46 +1,70 @@
 use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::time::Duration;
 
-use derive_more::Constructor;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    client::{Api, Client, FApi, SApi},
-    error::Result,
+    client::{Api, Client, DApi, FApi, SApi},
     models::*,
+    response::{ModeResult, Plain, ResponseMode, WithMetadata},
 };
 
-#[derive(Clone, Constructor, Debug)]
-pub struct Account<A: Api + AccountApi> {
+#[derive(Clone, Debug)]
+pub struct Account<A: Api + AccountApi, M = Plain> {
     client: Client<A>,
+    recv_window: Option<Duration>,
+    _mode: PhantomData<M>,
 }
 
 impl<A> Account<A>
 where
     A: Api + AccountApi,
 {
-    pub async fn balance(&self) -> Result<HashMap<String, Balance>, A::ErrorCode> {
-        self.client
-            .get(A::balance(), Empty::new())
-            .await
-            .map(|balances: Vec<Balance>| {
-                balances.into_iter().map(|b| (b.asset.clone(), b)).collect()
-            })
+    pub fn new(client: Client<A>) -> Self {
+        Self {
+            client,
+            recv_window: None,
+            _mode: PhantomData,
+        }
     }
 
-    pub async fn hedge_mode(&self) -> Result<bool, A::ErrorCode> {
-        #[derive(Deserialize)]
-        #[serde(rename_all = "camelCase")]
-        struct HedgeMode {
-            dual_side_position: bool,
+    /// Makes every call return a [`Response`](crate::response::Response) carrying the metadata
+    /// of the HTTP response, such as the used request weight, or a
+    /// [`ContextError`](crate::error::ContextError) carrying the request that failed.
+    pub fn with_metadata(self) -> Account<A, WithMetadata> {
+        Account {
+            client: self.client,
+            recv_window: self.recv_window,
+            _mode: PhantomData,
         }
+    }
+}
 
-        self.client
-            .get::<_, HedgeMode>(A::hedge_mode(), Empty::new())
-            .await
-            .map(|hm| hm.dual_side_position)
+impl<A, M> Account<A, M>
+where
+    A: Api + AccountApi,
+{
+    /// Sets the `recvWindow` of every signed request, instead of the default of the client.
+    pub fn recv_window(mut self, recv_window: Duration) -> Self {
+        self.recv_window = Some(recv_window);
+        self
     }
 
-    pub async fn listen_key(&self) -> Result<String, A::ErrorCode> {
+    fn signed<T>(&self, params: T) -> SignedRequest<T> {
+        SignedRequest {
+            params,
+            recv_window: self.recv_window.map(|w| w.as_millis() as u64),
+        }
+    }
+}
+
+impl<A, M> Account<A, M>
+where
+    A: Api + AccountApi,
+    M: ResponseMode,
+{
+    pub async fn listen_key(&self) -> ModeResult<M, String, A::ErrorCode> {
         #[derive(Deserialize)]
         #[serde(rename_all = "camelCase")]
         struct ListenKey {
@@ -50,150 +74,265 @@ where
         self.client
             .post::<_, ListenKey>(A::listen_key(), Empty::new())
             .await
-            .map(|lk| lk.listen_key)
+            .map_err(M::error)
+            .map(|r| M::output(r.map(|lk| lk.listen_key)))
+    }
+
+    pub async fn listen_key_close(&self) -> ModeResult<M, Empty, A::ErrorCode> {
+        self.client
+            .delete(A::listen_key(), Empty::new())
+            .await
+            .map_err(M::error)
+            .map(M::output)
+    }
+
+    pub async fn listen_key_keepalive(&self) -> ModeResult<M, Empty, A::ErrorCode> {
+        self.client
+            .put(A::listen_key(), Empty::new())
+            .await
+            .map_err(M::error)
+            .map(M::output)
     }
+}
 
-    pub async fn listen_key_close(&self) -> Result<Empty, A::ErrorCode> {
-        self.client.delete(A::listen_key(), Empty::new()).await
+impl<A, M> Account<A, M>
+where
+    A: Api + BalanceApi,
+    M: ResponseMode,
+{
+    pub async fn balance(&self) -> ModeResult<M, HashMap<String, Balance>, A::ErrorCode> {
+        self.client
+            .get(A::balance(), self.signed(Empty::new()))
+            .await
+            .map_err(M::error)
+            .map(|r| {
+                M::output(r.map(|balances: Vec<Balance>| {
+                    balances.into_iter().map(|b| (b.asset.clone(), b)).collect()
+                }))
+            })
     }
+}
+
+impl<A, M> Account<A, M>
+where
+    A: Api + PositionApi,
+    M: ResponseMode,
+{
+    pub async fn hedge_mode(&self) -> ModeResult<M, bool, A::ErrorCode> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct HedgeMode {
+            dual_side_position: bool,
+        }
 
-    pub async fn listen_key_keepalive(&self) -> Result<Empty, A::ErrorCode> {
-        self.client.put(A::listen_key(), Empty::new()).await
+        self.client
+            .get::<_, HedgeMode>(A::hedge_mode(), self.signed(Empty::new()))
+            .await
+            .map_err(M::error)
+            .map(|r| M::output(r.map(|hm| hm.dual_side_position)))
     }
 
-    pub async fn positions<S>(&self, symbol: Option<S>) -> Result<Vec<Position>, A::ErrorCode>
+    pub async fn positions<S>(
+        &self,
+        symbol: Option<S>,
+    ) -> ModeResult<M, Vec<Position>, A::ErrorCode>
     where
         S: AsRef<str>,
     {
         self.client
-            .get(A::positions(), SymbolRequest { symbol })
+            .get(A::positions(), self.signed(SymbolRequest { symbol }))
             .await
+            .map_err(M::error)
+            .map(M::output)
     }
 
-    pub async fn set_hedge_mode(&self, hedge_mode: bool) -> Result<(), A::ErrorCode> {
-        let _ = self
-            .client
-            .post::<_, serde_json::Value>(A::hedge_mode(), [("dualSidePosition", hedge_mode)])
-            .await;
-        Ok(())
+    pub async fn set_hedge_mode(&self, hedge_mode: bool) -> ModeResult<M, (), A::ErrorCode> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct HedgeModeRequest {
+            dual_side_position: bool,
+        }
+
+        self.client
+            .post::<_, serde_json::Value>(
+                A::hedge_mode(),
+                self.signed(HedgeModeRequest {
+                    dual_side_position: hedge_mode,
+                }),
+            )
+            .await
+            .map_err(M::error)
+            .map(|r| M::output(r.map(|_| ())))
     }
 }
 
 pub trait AccountApi {
+    fn listen_key() -> &'static str;
+}
+
+/// Implemented by the APIs that report futures wallet balances, i.e. not the Spot API.
+///
+/// ```compile_fail
+/// use binance_api::{account::Account, client::SClient};
+///
+/// # async fn balance() {
+/// let _ = Account::new(SClient::new()).balance().await;
+/// # }
+/// ```
+pub trait BalanceApi: AccountApi {
     fn balance() -> &'static str;
+}
+
+/// Implemented by the APIs that have positions, i.e. not the Spot API.
+///
+/// ```compile_fail
+/// use binance_api::{account::Account, client::SClient};
+///
+/// # async fn positions() {
+/// let _ = Account::new(SClient::new()).positions(Some("BTCUSDT")).await;
+/// # }
+/// ```
+pub trait PositionApi: AccountApi {
     fn hedge_mode() -> &'static str;
-    fn listen_key() -> &'static str;
     fn positions() -> &'static str;
 }
 
-impl AccountApi for FApi {
+impl AccountApi for DApi {
+    fn listen_key() -> &'static str {
+        "/dapi/v1/listenKey"
+    }
+}
+
+impl BalanceApi for DApi {
     fn balance() -> &'static str {
-        "/fapi/v2/balance"
+        "/dapi/v1/balance"
     }
+}
 
+impl PositionApi for DApi {
     fn hedge_mode() -> &'static str {
-        "/fapi/v1/positionSide/dual"
+        "/dapi/v1/positionSide/dual"
     }
 
-    fn listen_key() -> &'static str {
-        "/fapi/v1/listenKey"
+    fn positions() -> &'static str {
+        "/dapi/v1/positionRisk"
     }
+}
 
-    fn positions() -> &'static str {
-        "/fapi/v2/positionRisk"
+impl AccountApi for FApi {
+    fn listen_key() -> &'static str {
+        "/fapi/v1/listenKey"
     }
 }
 
-impl AccountApi for SApi {
+impl BalanceApi for FApi {
     fn balance() -> &'static str {
-        unimplemented!("Spot API does not support a balance API.");
+        "/fapi/v2/balance"
     }
+}
 
+impl PositionApi for FApi {
     fn hedge_mode() -> &'static str {
-        unimplemented!("Spot API does not support the notion of positions.");
+        "/fapi/v1/positionSide/dual"
     }
 
-    fn listen_key() -> &'static str {
-        "/api/v3/listenKey"
+    fn positions() -> &'static str {
+        "/fapi/v2/positionRisk"
     }
+}
 
-    fn positions() -> &'static str {
-        unimplemented!("Spot API does not support the notion of positions.");
+impl AccountApi for SApi {
+    fn listen_key() -> &'static str {
+        "/api/v3/listenKey"
     }
 }
 
+pub type DAccount = Account<DApi>;
 pub type FAccount = Account<FApi>;
 pub type SAccount = Account<SApi>;
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//     use crate::{AuthKeys, WSFClient};
-//     use futures::{future, stream::StreamExt};
-//     use tokio::time;
-
-//     fn api_keys() -> (String, String) {
-//         use std::env;
-
-//         (
-//             env::var("TRADER_API_KEY").unwrap(),
-//             env::var("TRADER_API_SECRET").unwrap(),
-//         )
-//     }
-
-//     #[tokio::test]
-//     async fn balance() {
-//         let (key, secret) = api_keys();
-//         let auth_keys = AuthKeys::new(key, secret);
-//         let client = Client::<FApi>::new(Some(auth_keys));
-//         let account = Account::new(client);
-
-//         eprintln!("{:?}", account.balance().await);
-//     }
-
-//     #[tokio::test]
-//     async fn hedge_mode() {
-//         let (key, secret) = api_keys();
-//         let auth_keys = AuthKeys::new(key, secret);
-//         let client = Client::<FApi>::new(Some(auth_keys));
-//         let account = Account::new(client);
-
-//         eprintln!("{:?}", account.hedge_mode().await);
-//         eprintln!("{:?}", account.set_hedge_mode(true).await);
-//     }
-
-//     #[tokio::test]
-//     async fn listen_key() {
-//         let (key, secret) = api_keys();
-//         let auth_keys = AuthKeys::new(key, secret);
-//         let client = Client::<FApi>::new(Some(auth_keys));
-//         let account = Account::new(client);
-//         let listen_key = account.listen_key().await.unwrap();
-
-//         let (client, stream) = WSFClient::user_data(listen_key).await.unwrap();
-//         {
-//             tokio::spawn(async move {
-//                 time::sleep(time::Duration::from_secs(10)).await;
-//                 client.close();
-//             });
-//         }
-//         stream
-//             .for_each(|e| {
-//                 if let Ok(e) = e {
-//                     eprintln!("{:?}", e);
-//                 }
-//                 future::ready(())
-//             })
-//             .await;
-//     }
-
-//     #[tokio::test]
-//     async fn positions() {
-//         let (key, secret) = api_keys();
-//         let auth_keys = AuthKeys::new(key, secret);
-//         let client = Client::<FApi>::new(Some(auth_keys));
-//         let account = Account::new(client);
-
-//         eprintln!("{:?}", account.positions(Some("ETHUSDT")).await);
-//     }
-// }
+#[cfg(test)]
+mod tests {
+    use futures::stream::StreamExt;
+
+    use super::*;
+    use crate::{
+        credentials::Credentials,
+        error::Error,
+        vcr::{self, Cassette},
+        ws::WSFClient,
+    };
+
+    fn cassette() -> Cassette {
+        vcr::replay("fapi_account.json")
+    }
+
+    fn account(cassette: &Cassette) -> FAccount {
+        let client = Client::builder()
+            .credentials(Credentials::new("api-key".into(), "secret-key".into()))
+            .transport(cassette.transport())
+            .build()
+            .unwrap();
+        Account::new(client)
+    }
+
+    #[tokio::test]
+    async fn balance() {
+        let balances = account(&cassette()).balance().await.unwrap();
+
+        assert_eq!(balances.len(), 2);
+        let usdt = &balances["USDT"];
+        assert_eq!(usdt.balance, "1250.3752".parse().unwrap());
+        assert_eq!(usdt.cross_unrealized_profit, "-12.5".parse().unwrap());
+        assert_eq!(usdt.max_withdraw_amount, "1187.8752".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn hedge_mode() {
+        let account = account(&cassette());
+
+        assert!(!account.hedge_mode().await.unwrap());
+        account.set_hedge_mode(true).await.unwrap();
+        match account.set_hedge_mode(true).await {
+            Err(Error::BadRequest(e)) => assert_eq!(e.msg(), "No need to change position side."),
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[tokio::test]
+    async fn listen_key() {
+        let cassette = cassette();
+        let listen_key = account(&cassette).listen_key().await.unwrap();
+        assert_eq!(listen_key.len(), 64);
+
+        let (client, stream) = WSFClient::builder()
+            .cassette(cassette)
+            .user_data(listen_key)
+            .await
+            .unwrap();
+
+        let events: Vec<_> = stream.map(|e| e.unwrap()).collect().await;
+        assert_eq!(events.len(), 1);
+        match &events[0].details {
+            WSEventDetails::AccountUpdate { details, .. } => {
+                assert_eq!(details.balances[0].asset, "USDT");
+                assert_eq!(details.positions[0].symbol, "ETHUSDT");
+            }
+            e => panic!("unexpected event {:?}", e),
+        }
+        assert!(client.is_closed());
+    }
+
+    #[tokio::test]
+    async fn positions() {
+        let positions = account(&cassette())
+            .positions(Some("ETHUSDT"))
+            .await
+            .unwrap();
+
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].symbol, "ETHUSDT");
+        assert_eq!(positions[0].position_amt, "1".parse().unwrap());
+        assert!(!positions[0].is_auto_add_margin);
+    }
+}
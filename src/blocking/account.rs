@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::{
+    account::{self, AccountApi, BalanceApi, PositionApi},
+    client::{Api, Client, DApi, FApi, SApi},
+    models::*,
+    response::{ModeResult, Plain, ResponseMode, WithMetadata},
+};
+
+use super::block_on;
+
+#[derive(Clone, Debug)]
+pub struct Account<A: Api + AccountApi, M = Plain> {
+    inner: account::Account<A, M>,
+}
+
+impl<A> Account<A>
+where
+    A: Api + AccountApi,
+{
+    pub fn new(client: Client<A>) -> Self {
+        Self {
+            inner: account::Account::new(client),
+        }
+    }
+
+    pub fn with_metadata(self) -> Account<A, WithMetadata> {
+        Account {
+            inner: self.inner.with_metadata(),
+        }
+    }
+}
+
+impl<A, M> Account<A, M>
+where
+    A: Api + AccountApi,
+    M: ResponseMode,
+{
+    pub fn recv_window(self, recv_window: Duration) -> Self {
+        Self {
+            inner: self.inner.recv_window(recv_window),
+        }
+    }
+
+    pub fn listen_key(&self) -> ModeResult<M, String, A::ErrorCode> {
+        block_on(self.inner.listen_key())
+    }
+
+    pub fn listen_key_close(&self) -> ModeResult<M, Empty, A::ErrorCode> {
+        block_on(self.inner.listen_key_close())
+    }
+
+    pub fn listen_key_keepalive(&self) -> ModeResult<M, Empty, A::ErrorCode> {
+        block_on(self.inner.listen_key_keepalive())
+    }
+}
+
+impl<A, M> Account<A, M>
+where
+    A: Api + BalanceApi,
+    M: ResponseMode,
+{
+    pub fn balance(&self) -> ModeResult<M, HashMap<String, Balance>, A::ErrorCode> {
+        block_on(self.inner.balance())
+    }
+}
+
+impl<A, M> Account<A, M>
+where
+    A: Api + PositionApi,
+    M: ResponseMode,
+{
+    pub fn hedge_mode(&self) -> ModeResult<M, bool, A::ErrorCode> {
+        block_on(self.inner.hedge_mode())
+    }
+
+    pub fn positions<S>(&self, symbol: Option<S>) -> ModeResult<M, Vec<Position>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.positions(symbol))
+    }
+
+    pub fn set_hedge_mode(&self, hedge_mode: bool) -> ModeResult<M, (), A::ErrorCode> {
+        block_on(self.inner.set_hedge_mode(hedge_mode))
+    }
+}
+
+pub type DAccount = Account<DApi>;
+pub type FAccount = Account<FApi>;
+pub type SAccount = Account<SApi>;
@@ -0,0 +1,50 @@
+use crate::{
+    client::{Api, Client},
+    exchange::{self, ExchangeApi},
+    models::*,
+    response::{ModeResult, Plain, ResponseMode, WithMetadata},
+};
+
+use super::block_on;
+
+#[derive(Clone, Debug)]
+pub struct Exchange<A: Api + ExchangeApi, M = Plain> {
+    inner: exchange::Exchange<A, M>,
+}
+
+impl<A> Exchange<A>
+where
+    A: Api + ExchangeApi,
+{
+    pub fn new(client: Client<A>) -> Self {
+        Self {
+            inner: exchange::Exchange::new(client),
+        }
+    }
+
+    pub fn with_metadata(self) -> Exchange<A, WithMetadata> {
+        Exchange {
+            inner: self.inner.with_metadata(),
+        }
+    }
+}
+
+impl<A, M> Exchange<A, M>
+where
+    A: Api + ExchangeApi,
+    M: ResponseMode,
+{
+    pub fn info(
+        &self,
+    ) -> ModeResult<M, ExchangeInfo<A::OrderType, A::SymbolDetails>, A::ErrorCode> {
+        block_on(self.inner.info())
+    }
+
+    pub fn ping(&self) -> ModeResult<M, Empty, A::ErrorCode> {
+        block_on(self.inner.ping())
+    }
+
+    pub fn time(&self) -> ModeResult<M, Time, A::ErrorCode> {
+        block_on(self.inner.time())
+    }
+}
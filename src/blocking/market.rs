@@ -0,0 +1,326 @@
+use std::result;
+
+use futures::stream::StreamExt;
+
+use crate::{
+    client::{Api, Client},
+    error::{KlinesRangeError, Result},
+    market::{self, FuturesMarketApi, MarketApi, SpotMarketApi},
+    models::*,
+    response::{ModeResult, Plain, ResponseMode, WithMetadata},
+};
+
+use super::block_on;
+
+#[derive(Clone, Debug)]
+pub struct Market<A: Api + MarketApi, M = Plain> {
+    inner: market::Market<A, M>,
+}
+
+impl<A> Market<A>
+where
+    A: Api + MarketApi,
+{
+    pub fn new(client: Client<A>) -> Self {
+        Self {
+            inner: market::Market::new(client),
+        }
+    }
+
+    pub fn with_metadata(self) -> Market<A, WithMetadata> {
+        Market {
+            inner: self.inner.with_metadata(),
+        }
+    }
+}
+
+impl<A, M> Market<A, M>
+where
+    A: Api + MarketApi,
+    M: ResponseMode,
+{
+    pub fn agg_trades<S>(
+        &self,
+        req: AggTradesRequest<S>,
+    ) -> ModeResult<M, Vec<AggTradesRecord>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.agg_trades(req))
+    }
+
+    /// The blocking counterpart of [`market::Market::agg_trades_range`].
+    pub fn agg_trades_range_iter<S>(
+        &self,
+        req: AggTradesRequest<S>,
+        until: Option<TradesBound>,
+    ) -> impl Iterator<Item = Result<AggTradesRecord, A::ErrorCode>> + '_
+    where
+        S: AsRef<str>,
+    {
+        let mut stream = Box::pin(self.inner.agg_trades_range(req, until));
+        std::iter::from_fn(move || block_on(stream.next()))
+    }
+
+    pub fn historical_trades<S>(
+        &self,
+        req: HistoricalTradesRequest<S>,
+    ) -> ModeResult<M, Vec<TradesRecord>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.historical_trades(req))
+    }
+
+    /// The blocking counterpart of [`market::Market::historical_trades_stream`], fetching a page
+    /// whenever the trades of the previous one are used up.
+    pub fn historical_trades_iter<S>(
+        &self,
+        req: HistoricalTradesRequest<S>,
+        direction: Direction,
+        until: Option<TradesBound>,
+    ) -> impl Iterator<Item = Result<TradesRecord, A::ErrorCode>> + '_
+    where
+        S: AsRef<str>,
+    {
+        let mut stream = Box::pin(self.inner.historical_trades_stream(req, direction, until));
+        std::iter::from_fn(move || block_on(stream.next()))
+    }
+
+    pub fn klines<S>(&self, req: KlinesRequest<S>) -> ModeResult<M, Vec<KlinesRecord>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.klines(req))
+    }
+
+    /// The blocking counterpart of [`market::Market::klines_range`].
+    pub fn klines_range_iter<S>(
+        &self,
+        req: KlinesRequest<S>,
+        options: KlinesRangeOptions,
+    ) -> impl Iterator<Item = result::Result<KlinesRecord, KlinesRangeError<A::ErrorCode>>> + '_
+    where
+        S: AsRef<str>,
+    {
+        let mut stream = Box::pin(self.inner.klines_range(req, options));
+        std::iter::from_fn(move || block_on(stream.next()))
+    }
+
+    pub fn order_book<S>(&self, req: OrderBookRequest<S>) -> ModeResult<M, OrderBook, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.order_book(req))
+    }
+
+    pub fn book_ticker<S>(
+        &self,
+        req: TickerRequest<S>,
+    ) -> ModeResult<M, Vec<BookTicker>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.book_ticker(req))
+    }
+
+    pub fn recent_trades<S>(
+        &self,
+        req: TradesRequest<S>,
+    ) -> ModeResult<M, Vec<TradesRecord>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.recent_trades(req))
+    }
+
+    pub fn ticker_24hr<S>(
+        &self,
+        req: TickerRequest<S>,
+    ) -> ModeResult<M, Vec<Ticker24hr>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.ticker_24hr(req))
+    }
+
+    pub fn ticker_price<S>(
+        &self,
+        req: TickerRequest<S>,
+    ) -> ModeResult<M, Vec<TickerPrice>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.ticker_price(req))
+    }
+}
+
+impl<A, M> Market<A, M>
+where
+    A: Api + FuturesMarketApi,
+    M: ResponseMode,
+{
+    pub fn continuous_klines<S>(
+        &self,
+        req: ContinuousKlinesRequest<S>,
+    ) -> ModeResult<M, Vec<KlinesRecord>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.continuous_klines(req))
+    }
+
+    pub fn funding_rate_history<S>(
+        &self,
+        req: FundingRateRequest<S>,
+    ) -> ModeResult<M, Vec<FundingRate>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.funding_rate_history(req))
+    }
+
+    /// The blocking counterpart of [`market::Market::funding_rate_history_stream`].
+    pub fn funding_rate_history_iter<S>(
+        &self,
+        req: FundingRateRequest<S>,
+    ) -> impl Iterator<Item = Result<FundingRate, A::ErrorCode>> + '_
+    where
+        S: AsRef<str>,
+    {
+        let mut stream = Box::pin(self.inner.funding_rate_history_stream(req));
+        std::iter::from_fn(move || block_on(stream.next()))
+    }
+
+    pub fn global_long_short_account_ratio<S>(
+        &self,
+        req: FuturesDataRequest<S>,
+    ) -> ModeResult<M, Vec<LongShortRatio>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.global_long_short_account_ratio(req))
+    }
+
+    pub fn index_price_klines<S>(
+        &self,
+        req: KlinesRequest<S>,
+    ) -> ModeResult<M, Vec<KlinesRecord>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.index_price_klines(req))
+    }
+
+    pub fn mark_price_klines<S>(
+        &self,
+        req: KlinesRequest<S>,
+    ) -> ModeResult<M, Vec<KlinesRecord>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.mark_price_klines(req))
+    }
+
+    pub fn open_interest<S>(&self, symbol: S) -> ModeResult<M, OpenInterest, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.open_interest(symbol))
+    }
+
+    pub fn open_interest_history<S>(
+        &self,
+        req: FuturesDataRequest<S>,
+    ) -> ModeResult<M, Vec<OpenInterestHist>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.open_interest_history(req))
+    }
+
+    pub fn premium_index<S>(
+        &self,
+        req: SymbolRequest<S>,
+    ) -> ModeResult<M, Vec<PremiumIndex>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.premium_index(req))
+    }
+
+    pub fn premium_index_klines<S>(
+        &self,
+        req: KlinesRequest<S>,
+    ) -> ModeResult<M, Vec<KlinesRecord>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.premium_index_klines(req))
+    }
+
+    pub fn taker_buy_sell_volume<S>(
+        &self,
+        req: FuturesDataRequest<S>,
+    ) -> ModeResult<M, Vec<TakerVolume>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.taker_buy_sell_volume(req))
+    }
+
+    pub fn top_long_short_account_ratio<S>(
+        &self,
+        req: FuturesDataRequest<S>,
+    ) -> ModeResult<M, Vec<LongShortRatio>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.top_long_short_account_ratio(req))
+    }
+
+    pub fn top_long_short_position_ratio<S>(
+        &self,
+        req: FuturesDataRequest<S>,
+    ) -> ModeResult<M, Vec<LongShortRatio>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.top_long_short_position_ratio(req))
+    }
+}
+
+impl<A, M> Market<A, M>
+where
+    A: Api + SpotMarketApi,
+    M: ResponseMode,
+{
+    pub fn avg_price<S>(&self, symbol: S) -> ModeResult<M, AvgPrice, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.avg_price(symbol))
+    }
+
+    pub fn rolling_window_ticker<S>(
+        &self,
+        req: TickerRequest<S>,
+        window_size: Option<WindowSize>,
+    ) -> ModeResult<M, Vec<WindowTicker>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.rolling_window_ticker(req, window_size))
+    }
+
+    pub fn trading_day_ticker<S>(
+        &self,
+        req: TickerRequest<S>,
+        time_zone: Option<&str>,
+    ) -> ModeResult<M, Vec<WindowTicker>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.trading_day_ticker(req, time_zone))
+    }
+}
@@ -0,0 +1,50 @@
+//! Synchronous versions of the [`Exchange`], [`Market`], [`Account`] and [`Trade`] providers.
+//!
+//! Requests are driven to completion on an internal Tokio runtime shared by all providers, so
+//! these must not be called from within an asynchronous context.
+
+#![allow(clippy::type_complexity)]
+
+use std::future::Future;
+use std::sync::OnceLock;
+
+use tokio::runtime::Runtime;
+
+pub use self::{account::*, exchange::*, market::*, trade::*};
+
+mod account;
+mod exchange;
+mod market;
+mod trade;
+
+fn block_on<F>(future: F) -> F::Output
+where
+    F: Future,
+{
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+    RUNTIME
+        .get_or_init(|| {
+            tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(1)
+                .thread_name("binance-api-blocking")
+                .enable_all()
+                .build()
+                .expect("failed to start the blocking runtime")
+        })
+        .block_on(future)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{client::FApi, vcr::replay_client};
+
+    #[test]
+    fn exchange_info() {
+        let client = replay_client::<FApi>("fapi_exchange_info.json");
+
+        let info = Exchange::new(client).info().unwrap();
+        assert_eq!(info.symbols[0].symbol, "BTCUSDT");
+    }
+}
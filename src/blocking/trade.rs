@@ -0,0 +1,164 @@
+use std::time::Duration;
+
+use crate::{
+    client::{Api, Client, DApi, FApi, SApi},
+    models::*,
+    response::{ModeResult, Plain, ResponseMode, WithMetadata},
+    trade::{self, AutoCancelApi, BatchOrderApi, LeverageApi, TradeApi},
+};
+
+use super::block_on;
+
+pub type DTrade = Trade<DApi>;
+pub type FTrade = Trade<FApi>;
+pub type STrade = Trade<SApi>;
+
+#[derive(Clone, Debug)]
+pub struct Trade<A: Api + TradeApi, M = Plain> {
+    inner: trade::Trade<A, M>,
+}
+
+impl<A> Trade<A>
+where
+    A: Api + TradeApi,
+{
+    pub fn new(client: Client<A>) -> Self {
+        Self {
+            inner: trade::Trade::new(client),
+        }
+    }
+
+    pub fn with_metadata(self) -> Trade<A, WithMetadata> {
+        Trade {
+            inner: self.inner.with_metadata(),
+        }
+    }
+}
+
+impl<A, M> Trade<A, M>
+where
+    A: Api + TradeApi,
+    M: ResponseMode,
+{
+    pub fn recv_window(self, recv_window: Duration) -> Self {
+        Self {
+            inner: self.inner.recv_window(recv_window),
+        }
+    }
+
+    pub fn all_orders<S>(
+        &self,
+        req: AllOrdersRequest<S>,
+    ) -> ModeResult<M, Order<A::OrderDetails, A::OrderType>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.all_orders(req))
+    }
+
+    pub fn cancel_all_open_orders<S>(&self, symbol: S) -> ModeResult<M, (), A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.cancel_all_open_orders(symbol))
+    }
+
+    pub fn cancel_order<S>(
+        &self,
+        symbol: S,
+        order_id: u64,
+    ) -> ModeResult<M, Order<A::OrderDetails, A::OrderType>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.cancel_order(symbol, order_id))
+    }
+
+    pub fn new_order<S>(
+        &self,
+        req: NewOrderRequest<A::OrderRequestDetails, A::OrderType, S>,
+    ) -> ModeResult<M, Order<A::OrderDetails, A::OrderType>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.new_order(req))
+    }
+
+    pub fn open_orders<S>(
+        &self,
+        symbol: Option<S>,
+    ) -> ModeResult<M, Vec<Order<A::OrderDetails, A::OrderType>>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.open_orders(symbol))
+    }
+
+    pub fn query_order<S>(
+        &self,
+        symbol: S,
+        order_id: u64,
+    ) -> ModeResult<M, Order<A::OrderDetails, A::OrderType>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.query_order(symbol, order_id))
+    }
+}
+
+impl<A, M> Trade<A, M>
+where
+    A: Api + AutoCancelApi,
+    M: ResponseMode,
+{
+    pub fn auto_cancel_all<S>(
+        &self,
+        symbol: S,
+        countdown_time: u64,
+    ) -> ModeResult<M, (), A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.auto_cancel_all(symbol, countdown_time))
+    }
+}
+
+impl<A, M> Trade<A, M>
+where
+    A: Api + BatchOrderApi,
+    M: ResponseMode,
+{
+    pub fn cancel_batch_orders<S>(
+        &self,
+        symbol: S,
+        order_ids: Vec<u64>,
+    ) -> ModeResult<M, Vec<Order<A::OrderDetails, A::OrderType>>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.cancel_batch_orders(symbol, order_ids))
+    }
+
+    pub fn new_batch_orders<S>(
+        &self,
+        batch_orders: Vec<NewOrderRequest<A::OrderRequestDetails, A::OrderType, S>>,
+    ) -> ModeResult<M, Vec<BatchOrder<A::OrderDetails, A::OrderType, A::ErrorCode>>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.new_batch_orders(batch_orders))
+    }
+}
+
+impl<A, M> Trade<A, M>
+where
+    A: Api + LeverageApi,
+    M: ResponseMode,
+{
+    pub fn leverage<S>(&self, symbol: S, leverage: u8) -> ModeResult<M, Leverage, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        block_on(self.inner.leverage(symbol, leverage))
+    }
+}
@@ -1,57 +1,70 @@
 use std::marker::PhantomData;
+use std::result;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use derive_more::Constructor;
-use hmac::{Hmac, Mac, NewMac};
-use serde::{de::DeserializeOwned, Serialize};
-use sha2::Sha256;
+use reqwest::{
+    header::{HeaderMap, HeaderValue, RETRY_AFTER},
+    Method, StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use crate::error::*;
+pub use crate::credentials::Credentials;
+use crate::{
+    clock::{Clock, ClockSync},
+    connection::ConnectionOptions,
+    error::*,
+    models::Time,
+    ratelimit::{RateLimiter, Weight},
+    response::Response,
+    retry::RetryPolicy,
+    rt,
+    signer::Signer,
+    transport::{HttpRequest, HttpResponse, ReqwestTransport, Transport},
+};
 
 const SAPI_HOST: &str = "https://api.binance.com";
+const SAPI_TESTNET_HOST: &str = "https://testnet.binance.vision";
 const FAPI_HOST: &str = "https://fapi.binance.com";
+const FAPI_TESTNET_HOST: &str = "https://testnet.binancefuture.com";
+const DAPI_HOST: &str = "https://dapi.binance.com";
+const DAPI_TESTNET_HOST: &str = "https://testnet.binancefuture.com";
+
+const DEFAULT_TIME_SYNC_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+// The largest part of an unexpected error response body that is kept in an error.
+const MAX_ERROR_BODY_LEN: usize = 512;
 
 macro_rules! http_verb {
-    ($method:ident) => {
+    ($method:ident, $verb:ident) => {
         #[allow(dead_code)]
-        pub(crate) async fn $method<I, O>(&self, path: &str, data: I) -> Result<O, A::ErrorCode>
+        pub(crate) async fn $method<I, O>(
+            &self,
+            path: &str,
+            data: I,
+        ) -> result::Result<Response<O>, ContextError<A::ErrorCode>>
         where
             I: Serialize,
             O: DeserializeOwned,
         {
-            let url = self.prepare_url(path, data)?;
-            let req = self.add_api_key(self.http.$method(&url));
-
-            self.send_request(req).await
+            self.request(Method::$verb, path, data).await
         }
     };
 }
 
+pub type DClient = Client<DApi>;
 pub type FClient = Client<FApi>;
 pub type SClient = Client<SApi>;
 
-#[derive(Clone, Constructor, Debug)]
-pub struct Credentials {
-    api_key: String,
-    secret_key: String,
-}
-
-impl Credentials {
-    fn api_key(&self) -> &str {
-        &self.api_key
-    }
-
-    fn sign(&self, data: impl AsRef<[u8]>) -> String {
-        let mut hmac = Hmac::<Sha256>::new_from_slice(self.secret_key.as_bytes())
-            .expect("secret_key should be of the correct byte length");
-        hmac.update(data.as_ref());
-        hex::encode(hmac.finalize().into_bytes())
-    }
-}
-
 #[derive(Clone, Debug)]
 pub struct Client<A: Api> {
-    creds: Option<Credentials>,
-    http: reqwest::Client,
+    clock: Clock,
+    host: String,
+    http: Arc<dyn Transport>,
+    rate_limiter: Option<RateLimiter>,
+    recv_window: Option<Duration>,
+    retry_policy: RetryPolicy,
+    signer: Option<Arc<dyn Signer>>,
     _marker: PhantomData<A>,
 }
 
@@ -59,79 +72,466 @@ impl<A> Client<A>
 where
     A: Api,
 {
+    /// # Panics
+    ///
+    /// Like `reqwest::Client::new`, panics if the TLS backend cannot be initialized. Use
+    /// [`Client::builder`] to handle that error instead.
     pub fn new() -> Self {
-        Self {
-            creds: None,
-            http: reqwest::Client::new(),
-            _marker: PhantomData,
-        }
+        Self::builder()
+            .build()
+            .expect("failed to initialize the TLS backend")
     }
 
+    /// # Panics
+    ///
+    /// Panics if the TLS backend cannot be initialized, like [`Client::new`].
     pub fn with_credentials(creds: Credentials) -> Self {
-        let creds = Some(creds);
+        Self::builder()
+            .credentials(creds)
+            .build()
+            .expect("failed to initialize the TLS backend")
+    }
 
-        Self {
-            creds,
-            http: reqwest::Client::new(),
-            _marker: PhantomData,
+    pub fn builder() -> ClientBuilder<A> {
+        ClientBuilder::new()
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.rate_limiter.as_ref()
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// The offset and round-trip time of the last server time measurement, if any.
+    pub fn clock_sync(&self) -> Option<ClockSync> {
+        self.clock.last_sync()
+    }
+
+    /// Measures the offset of the server clock, which is then applied to the `timestamp` of all
+    /// signed requests.
+    pub async fn sync_time(&self) -> Result<ClockSync, A::ErrorCode> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ServerTime {
+            server_time: Time,
         }
+
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire(Weight::new(1)).await;
+        }
+
+        self.clock.record_attempt();
+        let req = HttpRequest {
+            method: Method::GET,
+            url: format!("{}{}", self.host, A::time()),
+            headers: HeaderMap::new(),
+        };
+        let sent_at = chrono::Utc::now();
+        let started = Instant::now();
+        let st: Response<ServerTime> = self.send_request(A::time(), req).await?;
+
+        Ok(self
+            .clock
+            .record(sent_at, started.elapsed(), st.data.server_time))
     }
 
-    fn add_api_key(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
-        if let Some(keys) = &self.creds {
-            builder.header("X-MBX-APIKEY", keys.api_key())
-        } else {
-            builder
+    fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = self
+            .signer
+            .as_ref()
+            .and_then(|signer| HeaderValue::from_str(signer.api_key()).ok())
+        {
+            headers.insert("X-MBX-APIKEY", value);
         }
+        headers
     }
 
-    fn prepare_url<I>(&self, path: &str, data: I) -> Result<String, A::ErrorCode>
+    async fn prepare_url(&self, path: &str, mut query: String) -> Result<String, A::ErrorCode> {
+        if let Some(signer) = &self.signer {
+            if let Some(recv_window) = self.recv_window {
+                if !query.split('&').any(|p| p.starts_with("recvWindow=")) {
+                    query = join_query(query, format!("recvWindow={}", recv_window.as_millis()));
+                }
+            }
+            query = join_query(query, format!("timestamp={}", self.clock.now_millis()));
+            let signature = signer.sign(query.clone()).await.map_err(Error::Signing)?;
+            let signature: String =
+                url::form_urlencoded::byte_serialize(signature.as_bytes()).collect();
+            query = format!("{}&signature={}", query, signature);
+        }
+
+        Ok(format!("{}{}?{}", self.host, path, query))
+    }
+
+    async fn request<I, O>(
+        &self,
+        method: Method,
+        path: &str,
+        data: I,
+    ) -> result::Result<Response<O>, ContextError<A::ErrorCode>>
     where
         I: Serialize,
+        O: DeserializeOwned,
+    {
+        let context = || ErrorContext {
+            method: method.clone(),
+            path: path.to_string(),
+            status: None,
+            body: None,
+        };
+        let query = serde_urlencoded::to_string(data)
+            .map_err(|e| Error::from(e).with_context(context()))?;
+        let idempotent = method == Method::GET || method == Method::PUT;
+        let mut attempt = 1;
+
+        loop {
+            if self.signer.is_some() && self.clock.needs_sync() {
+                let _syncing = self.clock.lock_sync().await;
+                if self.clock.needs_sync() {
+                    if let Err(e) = self.sync_time().await {
+                        tracing::warn!(error = %e, "failed to synchronize with the server time");
+                    }
+                }
+            }
+
+            if let Some(limiter) = &self.rate_limiter {
+                limiter.acquire(Weight::of(&method, path, &query)).await;
+            }
+
+            // Signed requests are re-signed on every attempt to refresh their timestamp.
+            let req = HttpRequest {
+                method: method.clone(),
+                url: self
+                    .prepare_url(path, query.clone())
+                    .await
+                    .map_err(|e| e.with_context(context()))?,
+                headers: self.headers(),
+            };
+
+            match self.send_request(path, req).await {
+                Err(e) => match self.retry_policy.backoff(attempt, idempotent, &e.error) {
+                    Some(delay) => {
+                        tracing::debug!(%method, path, attempt, ?delay, error = %e, "retrying");
+                        rt::sleep(delay).await;
+                        attempt += 1;
+                    }
+                    None => return Err(e),
+                },
+                result => return result,
+            }
+        }
+    }
+
+    async fn send_request<O>(
+        &self,
+        path: &str,
+        req: HttpRequest,
+    ) -> result::Result<Response<O>, ContextError<A::ErrorCode>>
+    where
+        O: DeserializeOwned,
     {
-        let mut query = serde_urlencoded::to_string(data)?;
-
-        if let Some(keys) = &self.creds {
-            query = format!(
-                "{}&timestamp={}",
-                query,
-                chrono::Utc::now().timestamp_millis()
-            );
-            query = format!("{}&signature={}", query, keys.sign(&query));
+        let mut context = ErrorContext {
+            method: req.method.clone(),
+            path: path.to_string(),
+            status: None,
+            body: None,
+        };
+        let started = Instant::now();
+        let resp = match self.http.send(req).await {
+            Ok(resp) => resp,
+            Err(e) => return Err(Error::from(e).with_context(context)),
+        };
+        let latency = started.elapsed();
+
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.update(&resp.headers);
         }
 
-        Ok(format!("{}{}?{}", A::host(), path, query))
+        let HttpResponse {
+            status,
+            headers,
+            body,
+        } = resp;
+        Self::decode_response(status, headers, &body, latency).map_err(|e| {
+            context.status = Some(status);
+            if let Error::ResponseDecoding(_) = e {
+                context.body = Some(String::from_utf8_lossy(&body).into_owned());
+            }
+            e.with_context(context)
+        })
     }
 
-    async fn send_request<O>(&self, req: reqwest::RequestBuilder) -> Result<O, A::ErrorCode>
+    #[allow(clippy::result_large_err)]
+    fn decode_response<O>(
+        status: StatusCode,
+        headers: HeaderMap,
+        body: &[u8],
+        latency: Duration,
+    ) -> Result<Response<O>, A::ErrorCode>
     where
         O: DeserializeOwned,
     {
-        let resp = req.send().await?;
-
-        match resp.status().as_u16() {
-            200 => Ok(resp.json().await?),
-            403 => Err(Error::FirewallLimitReached),
-            418 => Err(Error::IPAddressBanned),
-            429 => Err(Error::RequestRateLimitReached),
-            400..=499 => Err(Error::BadRequest(resp.json().await?)),
+        let retry_after = headers
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse().ok())
+            .map(Duration::from_secs);
+
+        match status.as_u16() {
+            403 => Err(Error::FirewallLimitReached { retry_after }),
+            418 => Err(Error::IPAddressBanned { retry_after }),
+            429 => Err(Error::RequestRateLimitReached { retry_after }),
             503 => Err(Error::ApiTimeout),
-            500..=599 => Err(Error::Server(resp.json().await?)),
-            _ => Ok(resp.error_for_status()?.json().await?),
+            400..=599 => match serde_json::from_slice(body) {
+                Ok(e) if status.is_client_error() => Err(Error::BadRequest(e)),
+                Ok(e) => Err(Error::Server(e)),
+                Err(_) => Err(Error::UnexpectedResponse {
+                    status,
+                    headers,
+                    body: truncate_body(body),
+                }),
+            },
+            _ => Ok(Response {
+                data: serde_json::from_slice(body)?,
+                status,
+                headers,
+                latency,
+            }),
+        }
+    }
+
+    http_verb!(delete, DELETE);
+    http_verb!(get, GET);
+    http_verb!(patch, PATCH);
+    http_verb!(post, POST);
+    http_verb!(put, PUT);
+}
+
+fn join_query(query: String, param: String) -> String {
+    if query.is_empty() {
+        param
+    } else {
+        format!("{}&{}", query, param)
+    }
+}
+
+// Keeps at most `MAX_ERROR_BODY_LEN` bytes of an unexpected response body, e.g. an HTML page.
+fn truncate_body(body: &[u8]) -> String {
+    let body = String::from_utf8_lossy(body);
+    if body.len() <= MAX_ERROR_BODY_LEN {
+        return body.into_owned();
+    }
+
+    let mut end = MAX_ERROR_BODY_LEN;
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}...", &body[..end])
+}
+
+impl<A> Default for Client<A>
+where
+    A: Api,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds a [`Client`], targeting the production host of the API unless told otherwise.
+#[derive(Clone, Debug)]
+pub struct ClientBuilder<A: Api> {
+    connection: ConnectionOptions,
+    host: Option<String>,
+    rate_limiter: Option<RateLimiter>,
+    recv_window: Option<Duration>,
+    retry_policy: RetryPolicy,
+    signer: Option<Arc<dyn Signer>>,
+    time_sync_interval: Option<Duration>,
+    transport: Option<Arc<dyn Transport>>,
+    _marker: PhantomData<A>,
+}
+
+impl<A> ClientBuilder<A>
+where
+    A: Api,
+{
+    pub fn new() -> Self {
+        Self {
+            connection: ConnectionOptions::default(),
+            host: None,
+            rate_limiter: None,
+            recv_window: None,
+            retry_policy: RetryPolicy::default(),
+            signer: None,
+            time_sync_interval: Some(DEFAULT_TIME_SYNC_INTERVAL),
+            transport: None,
+            _marker: PhantomData,
         }
     }
 
-    http_verb!(delete);
-    http_verb!(get);
-    http_verb!(patch);
-    http_verb!(post);
-    http_verb!(put);
+    pub fn credentials(self, creds: Credentials) -> Self {
+        self.signer(creds)
+    }
+
+    /// Signs requests with `signer` instead of in-memory [`Credentials`].
+    pub fn signer<S>(mut self, signer: S) -> Self
+    where
+        S: Signer,
+    {
+        self.signer = Some(Arc::new(signer));
+        self
+    }
+
+    /// Sets the base URL of the REST API, e.g. `http://127.0.0.1:8080`.
+    pub fn host<S>(mut self, host: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.host = Some(host.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    pub fn environment<E>(self, env: E) -> Self
+    where
+        E: Environment<A>,
+    {
+        self.host(env.host())
+    }
+
+    /// Delays requests that would exceed the rate limits tracked by `rate_limiter`, e.g.
+    /// `RateLimiter::new(&exchange_info.rate_limits)`.
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Sets the default `recvWindow` of signed requests, unless a request sets its own.
+    pub fn recv_window(mut self, recv_window: Duration) -> Self {
+        self.recv_window = Some(recv_window);
+        self
+    }
+
+    /// Re-measures the offset of the server clock before a signed request whenever the last
+    /// measurement is older than `interval`, which defaults to 10 minutes.
+    pub fn time_sync_interval(mut self, interval: Duration) -> Self {
+        self.time_sync_interval = Some(interval);
+        self
+    }
+
+    /// Replaces the default [`RetryPolicy`], which retries idempotent requests up to 3 times.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets the timeouts, proxy, connection pool and TLS settings of the default
+    /// [`ReqwestTransport`].
+    pub fn connection(mut self, connection: ConnectionOptions) -> Self {
+        self.connection = connection;
+        self
+    }
+
+    /// Sends requests with `http` as is, ignoring the [`ConnectionOptions`].
+    pub fn http_client(self, http: reqwest::Client) -> Self {
+        self.transport(ReqwestTransport::new(http))
+    }
+
+    /// Sends requests through `transport` instead of the default [`ReqwestTransport`], ignoring
+    /// the [`ConnectionOptions`].
+    pub fn transport<T>(mut self, transport: T) -> Self
+    where
+        T: Transport,
+    {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Fails if the TLS backend of the default [`ReqwestTransport`] cannot be initialized.
+    pub fn build(self) -> result::Result<Client<A>, ConnectionError> {
+        Ok(Client {
+            clock: Clock::new(self.time_sync_interval),
+            host: self.host.unwrap_or_else(|| A::host().to_string()),
+            http: match self.transport {
+                Some(transport) => transport,
+                None => Arc::new(ReqwestTransport::new(self.connection.http_client()?)),
+            },
+            rate_limiter: self.rate_limiter,
+            recv_window: self.recv_window,
+            retry_policy: self.retry_policy,
+            signer: self.signer,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<A> Default for ClientBuilder<A>
+where
+    A: Api,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A preset deployment of the Binance APIs, providing the base URL for the API `A`.
+pub trait Environment<A> {
+    fn host(&self) -> &str;
+}
+
+/// The Spot API testnet, see <https://testnet.binance.vision>.
+#[derive(Clone, Copy, Debug)]
+pub struct SpotTestnet;
+
+impl Environment<SApi> for SpotTestnet {
+    fn host(&self) -> &str {
+        SAPI_TESTNET_HOST
+    }
+}
+
+/// The USDⓈ-M and COIN-M Futures API testnet, see <https://testnet.binancefuture.com>.
+#[derive(Clone, Copy, Debug)]
+pub struct FuturesTestnet;
+
+impl Environment<FApi> for FuturesTestnet {
+    fn host(&self) -> &str {
+        FAPI_TESTNET_HOST
+    }
+}
+
+impl Environment<DApi> for FuturesTestnet {
+    fn host(&self) -> &str {
+        DAPI_TESTNET_HOST
+    }
 }
 
 pub trait Api: Clone + Send + Sync {
     type ErrorCode: ApiCode + DeserializeOwned;
 
     fn host() -> &'static str;
+    fn time() -> &'static str;
+}
+
+/// The COIN-M delivery futures API.
+#[derive(Clone, Debug)]
+pub struct DApi;
+impl Api for DApi {
+    type ErrorCode = FApiCode;
+
+    fn host() -> &'static str {
+        DAPI_HOST
+    }
+
+    fn time() -> &'static str {
+        "/dapi/v1/time"
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -142,6 +542,10 @@ impl Api for FApi {
     fn host() -> &'static str {
         FAPI_HOST
     }
+
+    fn time() -> &'static str {
+        "/fapi/v1/time"
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -152,4 +556,345 @@ impl Api for SApi {
     fn host() -> &'static str {
         SAPI_HOST
     }
+
+    fn time() -> &'static str {
+        "/api/v3/time"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    use futures::future::{BoxFuture, FutureExt};
+    use reqwest::StatusCode;
+
+    use super::*;
+    use crate::{
+        account::FAccount,
+        exchange::Exchange,
+        models::Empty,
+        transport::{BoxError, HttpResponse},
+    };
+
+    #[derive(Debug, Default)]
+    struct MockTransport {
+        requests: Mutex<Vec<HttpRequest>>,
+        responses: Mutex<VecDeque<(u16, &'static str)>>,
+    }
+
+    impl MockTransport {
+        fn new(responses: &[(u16, &'static str)]) -> Arc<Self> {
+            Arc::new(Self {
+                requests: Default::default(),
+                responses: Mutex::new(responses.iter().copied().collect()),
+            })
+        }
+    }
+
+    impl Transport for MockTransport {
+        fn send(
+            &self,
+            req: HttpRequest,
+        ) -> BoxFuture<'_, std::result::Result<HttpResponse, BoxError>> {
+            self.requests.lock().unwrap().push(req);
+            let (status, body) = self.responses.lock().unwrap().pop_front().unwrap();
+            let mut headers = HeaderMap::new();
+            headers.insert(RETRY_AFTER, HeaderValue::from_static("0"));
+
+            futures::future::ready(Ok(HttpResponse {
+                status: StatusCode::from_u16(status).unwrap(),
+                headers,
+                body: body.as_bytes().to_vec(),
+            }))
+            .boxed()
+        }
+    }
+
+    const SERVER_TIME: (u16, &str) = (200, r#"{"serverTime":1654041600000}"#);
+
+    #[test]
+    fn host() {
+        let client = FClient::builder().build().unwrap();
+        assert_eq!(client.host(), "https://fapi.binance.com");
+
+        let client = FClient::builder()
+            .host("http://127.0.0.1:8080/")
+            .build()
+            .unwrap();
+        assert_eq!(client.host(), "http://127.0.0.1:8080");
+    }
+
+    #[test]
+    fn environment() {
+        let client = SClient::builder().environment(SpotTestnet).build().unwrap();
+        assert_eq!(client.host(), "https://testnet.binance.vision");
+
+        let client = FClient::builder()
+            .environment(FuturesTestnet)
+            .build()
+            .unwrap();
+        assert_eq!(client.host(), "https://testnet.binancefuture.com");
+
+        let client = DClient::builder()
+            .environment(FuturesTestnet)
+            .build()
+            .unwrap();
+        assert_eq!(client.host(), "https://testnet.binancefuture.com");
+    }
+
+    #[tokio::test]
+    async fn environment_request() {
+        let transport = MockTransport::new(&[(200, "{}")]);
+        let client = FClient::builder()
+            .environment(FuturesTestnet)
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let _: Response<Empty> = client.get("/fapi/v1/ping", Empty::new()).await.unwrap();
+
+        let requests = transport.requests.lock().unwrap();
+        assert!(requests[0]
+            .url
+            .starts_with("https://testnet.binancefuture.com/fapi/v1/ping"));
+    }
+
+    #[tokio::test]
+    async fn signed_request() {
+        let transport = MockTransport::new(&[SERVER_TIME, (200, "{}")]);
+        let client = FClient::builder()
+            .credentials(Credentials::new("key".into(), "secret".into()))
+            .host("http://localhost")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let _: Response<Empty> = client.get("/fapi/v1/test", [("a", 1)]).await.unwrap();
+
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests[0].url, "http://localhost/fapi/v1/time");
+        assert!(requests[1]
+            .url
+            .starts_with("http://localhost/fapi/v1/test?a=1&timestamp="));
+        assert!(requests[1].url.contains("&signature="));
+        assert_eq!(requests[1].headers["X-MBX-APIKEY"], "key");
+        assert!(client.clock_sync().is_some());
+    }
+
+    #[tokio::test]
+    async fn time_sync() {
+        let transport = MockTransport::new(&[SERVER_TIME, (200, "{}"), (200, "{}")]);
+        let client = FClient::builder()
+            .credentials(Credentials::new("key".into(), "secret".into()))
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let (a, b) = futures::join!(
+            client.get::<_, Empty>("/fapi/v1/test", Empty::new()),
+            client.get::<_, Empty>("/fapi/v1/test", Empty::new()),
+        );
+        assert!(a.is_ok() && b.is_ok());
+
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].url.ends_with("/fapi/v1/time"));
+    }
+
+    #[tokio::test]
+    async fn recv_window() {
+        let transport = MockTransport::new(&[
+            SERVER_TIME,
+            (200, r#"{"dualSidePosition":true}"#),
+            (200, r#"{"dualSidePosition":true}"#),
+        ]);
+        let client = FClient::builder()
+            .credentials(Credentials::new("key".into(), "secret".into()))
+            .recv_window(Duration::from_secs(5))
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        let account = FAccount::new(client);
+
+        account.hedge_mode().await.unwrap();
+        account
+            .recv_window(Duration::from_secs(2))
+            .hedge_mode()
+            .await
+            .unwrap();
+
+        let requests = transport.requests.lock().unwrap();
+        assert!(requests[1].url.contains("?recvWindow=5000&timestamp="));
+        assert!(requests[2].url.contains("?recvWindow=2000&timestamp="));
+    }
+
+    #[derive(Debug)]
+    struct RemoteSigner {
+        fail: bool,
+    }
+
+    impl Signer for RemoteSigner {
+        fn api_key(&self) -> &str {
+            "remote-key"
+        }
+
+        fn sign(&self, payload: String) -> BoxFuture<'_, std::result::Result<String, BoxError>> {
+            async move {
+                if self.fail {
+                    return Err("signing service unavailable".into());
+                }
+                assert!(payload.starts_with("a=1&timestamp="));
+                Ok("c2ln/+=".to_string())
+            }
+            .boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn external_signer() {
+        let transport = MockTransport::new(&[SERVER_TIME, (200, "{}")]);
+        let client = FClient::builder()
+            .signer(RemoteSigner { fail: false })
+            .host("http://localhost")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let _: Response<Empty> = client.get("/fapi/v1/test", [("a", 1)]).await.unwrap();
+
+        {
+            let requests = transport.requests.lock().unwrap();
+            assert!(requests[1].url.ends_with("&signature=c2ln%2F%2B%3D"));
+            assert_eq!(requests[1].headers["X-MBX-APIKEY"], "remote-key");
+        }
+
+        let client = FClient::builder()
+            .signer(RemoteSigner { fail: true })
+            .transport(MockTransport::new(&[SERVER_TIME]))
+            .build()
+            .unwrap();
+        let resp: result::Result<Response<Empty>, _> =
+            client.get("/fapi/v1/test", Empty::new()).await;
+        assert!(matches!(resp.unwrap_err().error, Error::Signing(_)));
+    }
+
+    #[tokio::test]
+    async fn error_context() {
+        let transport = MockTransport::new(&[
+            (400, r#"{"code":-2019,"msg":"Margin is insufficient."}"#),
+            (200, "<html>"),
+        ]);
+        let client = FClient::builder().transport(transport).build().unwrap();
+
+        let err = client
+            .post::<_, Empty>("/fapi/v1/order", [("symbol", "BTCUSDT")])
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InsufficientBalance);
+        assert!(matches!(err.error, Error::BadRequest(_)));
+        let context = &err.context;
+        assert_eq!(context.method, Method::POST);
+        assert_eq!(context.path, "/fapi/v1/order");
+        assert_eq!(context.status, Some(StatusCode::BAD_REQUEST));
+        assert_eq!(context.body, None);
+
+        let err = client
+            .get::<_, Empty>("/fapi/v1/time", Empty::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(err.error, Error::ResponseDecoding(_)));
+        assert_eq!(err.context.body.as_deref(), Some("<html>"));
+        assert_eq!(err.to_string(), format!("GET /fapi/v1/time: {}", err.error));
+    }
+
+    #[tokio::test]
+    async fn provider_errors() {
+        let transport = MockTransport::new(&[
+            (400, r#"{"code":-1100,"msg":"Illegal characters found."}"#),
+            (400, r#"{"code":-1100,"msg":"Illegal characters found."}"#),
+        ]);
+        let client = FClient::builder().transport(transport).build().unwrap();
+
+        let err = Exchange::new(client.clone()).ping().await.unwrap_err();
+        assert!(matches!(err, Error::BadRequest(_)));
+
+        let err = Exchange::new(client)
+            .with_metadata()
+            .ping()
+            .await
+            .unwrap_err();
+        assert!(matches!(err.error, Error::BadRequest(_)));
+        assert_eq!(err.context.path, "/fapi/v1/ping");
+    }
+
+    #[tokio::test]
+    async fn unexpected_response() {
+        let transport = MockTransport::new(&[
+            (403, ""),
+            (502, "<html><body>502 Bad Gateway</body></html>"),
+        ]);
+        let client = FClient::builder()
+            .transport(transport)
+            .retry_policy(RetryPolicy::never())
+            .build()
+            .unwrap();
+
+        let err = client
+            .get::<_, Empty>("/fapi/v1/time", Empty::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.error,
+            Error::FirewallLimitReached {
+                retry_after: Some(d)
+            } if d.is_zero()
+        ));
+
+        let err = client
+            .get::<_, Empty>("/fapi/v1/time", Empty::new())
+            .await
+            .unwrap_err();
+        match &err.error {
+            Error::UnexpectedResponse { status, body, .. } => {
+                assert_eq!(*status, StatusCode::BAD_GATEWAY);
+                assert_eq!(body, "<html><body>502 Bad Gateway</body></html>");
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+        assert_eq!(err.error.kind(), ErrorKind::UnknownOrderOutcome);
+        assert_eq!(err.kind(), ErrorKind::Transient);
+
+        let body = "é".repeat(MAX_ERROR_BODY_LEN);
+        let truncated = truncate_body(body.as_bytes());
+        assert!(truncated.len() <= MAX_ERROR_BODY_LEN + 3);
+        assert!(truncated.ends_with("é..."));
+    }
+
+    #[tokio::test]
+    async fn retry() {
+        let transport = MockTransport::new(&[(503, ""), (429, ""), (200, "{}")]);
+        let client = FClient::builder()
+            .transport(transport.clone())
+            .retry_policy(RetryPolicy::new().initial_backoff(Duration::from_millis(1)))
+            .build()
+            .unwrap();
+
+        let resp: result::Result<Response<Empty>, _> =
+            client.get("/fapi/v1/test", Empty::new()).await;
+        assert!(resp.is_ok());
+        assert_eq!(transport.requests.lock().unwrap().len(), 3);
+
+        let transport = MockTransport::new(&[(503, "")]);
+        let client = FClient::builder()
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let resp: result::Result<Response<Empty>, _> =
+            client.post("/fapi/v1/order", Empty::new()).await;
+        assert!(matches!(resp.unwrap_err().error, Error::ApiTimeout));
+        assert_eq!(transport.requests.lock().unwrap().len(), 1);
+    }
 }
@@ -0,0 +1,110 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use futures::lock::{Mutex as AsyncMutex, MutexGuard};
+
+use crate::models::Time;
+
+/// The result of measuring the local clock against the server time.
+#[derive(Clone, Copy, Debug)]
+pub struct ClockSync {
+    /// How far the server clock is ahead of the local clock.
+    pub offset: chrono::Duration,
+    pub round_trip_time: Duration,
+    pub server_time: Time,
+}
+
+/// Tracks the offset of the server clock, shared by all clones of a
+/// [`Client`](crate::client::Client).
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Clock {
+    interval: Option<Duration>,
+    last_sync: Arc<Mutex<Option<ClockSync>>>,
+    // The last measurement, successful or not, so that a failing one is not repeated by every
+    // request.
+    last_attempt: Arc<Mutex<Option<Instant>>>,
+    syncing: Arc<AsyncMutex<()>>,
+}
+
+impl Clock {
+    pub fn new(interval: Option<Duration>) -> Self {
+        Self {
+            interval,
+            last_sync: Default::default(),
+            last_attempt: Default::default(),
+            syncing: Default::default(),
+        }
+    }
+
+    pub fn last_sync(&self) -> Option<ClockSync> {
+        *self.last_sync.lock().unwrap()
+    }
+
+    pub fn needs_sync(&self) -> bool {
+        match (self.interval, *self.last_attempt.lock().unwrap()) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(interval), Some(attempted_at)) => attempted_at.elapsed() >= interval,
+        }
+    }
+
+    /// Waits until no other measurement is in flight. Callers check [`Clock::needs_sync`] again
+    /// once they hold the guard, as the clock may have been synchronized in the meantime.
+    pub async fn lock_sync(&self) -> MutexGuard<'_, ()> {
+        self.syncing.lock().await
+    }
+
+    pub fn record_attempt(&self) {
+        *self.last_attempt.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// Records a server time that was requested at `sent_at` and received after `round_trip_time`,
+    /// assuming the server stamped it halfway through the round trip.
+    pub fn record(
+        &self,
+        sent_at: DateTime<Utc>,
+        round_trip_time: Duration,
+        server_time: Time,
+    ) -> ClockSync {
+        let half_rtt = chrono::Duration::from_std(round_trip_time / 2)
+            .unwrap_or_else(|_| chrono::Duration::zero());
+        let sync = ClockSync {
+            offset: *server_time - (sent_at + half_rtt),
+            round_trip_time,
+            server_time,
+        };
+
+        *self.last_sync.lock().unwrap() = Some(sync);
+        self.record_attempt();
+        sync
+    }
+
+    /// The current time in milliseconds, corrected by the last measured offset.
+    pub fn now_millis(&self) -> i64 {
+        let offset = self
+            .last_sync()
+            .map(|sync| sync.offset)
+            .unwrap_or_else(chrono::Duration::zero);
+        (Utc::now() + offset).timestamp_millis()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record() {
+        let clock = Clock::new(Some(Duration::from_secs(60)));
+        assert!(clock.needs_sync());
+
+        let sent_at = Utc::now();
+        let server_time = Time(sent_at + chrono::Duration::milliseconds(1_050));
+        let sync = clock.record(sent_at, Duration::from_millis(100), server_time);
+
+        assert_eq!(sync.offset, chrono::Duration::milliseconds(1_000));
+        assert!(!clock.needs_sync());
+        assert!(clock.now_millis() >= sent_at.timestamp_millis() + 1_000);
+    }
+}
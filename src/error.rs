@@ -3,19 +3,27 @@ use std::convert::TryFrom;
 use std::fmt;
 use std::marker::{Copy, PhantomData};
 use std::result;
+use std::time::Duration;
 
 use derive_more::Constructor;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
+use reqwest::{header::HeaderMap, Method, StatusCode};
 use serde::{de, Deserialize};
 use serde_repr::Deserialize_repr;
 
+use crate::{models::Time, transport::BoxError};
+
 pub type Result<T, C> = result::Result<T, Error<C>>;
 
-#[derive(Clone, Copy, Debug, Deserialize_repr, FromPrimitive)]
+/// Codes shared by all REST APIs, taken from the error code list of the Spot API, see
+/// <https://developers.binance.com/docs/binance-spot-api-docs/errors>. The table is maintained
+/// by hand and may lag behind the list; missing codes are reported as [`Code::Unknown`].
+#[derive(Clone, Copy, Debug, Default, Deserialize_repr, FromPrimitive)]
 #[repr(i16)]
 pub enum CommonCode {
     // 10xx General server or network issues
+    #[default]
     Unknown = -1000,
     Disconnected = -1001,
     Unauthorized = -1002,
@@ -24,6 +32,7 @@ pub enum CommonCode {
     NoSuchIp = -1005,
     UnexpectedResponse = -1006,
     Timeout = -1007,
+    ServerBusy = -1008,
     ErrorMessageReceived = -1010,
     IpNotOnWhiteList = -1011,
     InvalidMessage = -1013,
@@ -34,6 +43,9 @@ pub enum CommonCode {
     InvalidTimestamp = -1021,
     InvalidSignature = -1022,
     StartTimeGreaterThanEndTime = -1023,
+    CompIdInUse = -1033,
+    TooManyConnections = -1034,
+    LoggedOut = -1035,
     NotFoundOrAllowed = -1099,
 
     // 11xx - 2xxx Request issues
@@ -58,11 +70,56 @@ pub enum CommonCode {
     EmptyOriginalClientOrderId = -1119,
     BadInterval = -1120,
     BadSymbol = -1121,
+    InvalidSymbolStatus = -1122,
     InvalidListenKey = -1125,
+    AssetNotSupported = -1126,
     MoreThanXxHours = -1127,
     OptionalParametersBadCombination = -1128,
     InvalidParameter = -1130,
     BadReceiveWindow = -1131,
+    BadStrategyType = -1134,
+    InvalidJson = -1135,
+    InvalidNewOrderResponseType = -1136,
+    InvalidTickerType = -1139,
+    InvalidCancelRestrictions = -1145,
+    DuplicateSymbols = -1151,
+    InvalidSbeHeader = -1152,
+    UnsupportedSchemaId = -1153,
+    SbeDisabled = -1155,
+    OcoOrderTypeRejected = -1158,
+    OcoIcebergQuantityTimeInForce = -1160,
+    DeprecatedSchema = -1161,
+    BuyOcoLimitMustBeBelow = -1165,
+    SellOcoLimitMustBeAbove = -1166,
+    BothOcoOrdersCannotBeLimit = -1168,
+    InvalidTagNumber = -1169,
+    TagNotDefinedInMessage = -1170,
+    TagAppearsMoreThanOnce = -1171,
+    TagOutOfOrder = -1172,
+    GroupFieldsOutOfOrder = -1173,
+    InvalidComponent = -1174,
+    ResetSeqNumSupport = -1175,
+    AlreadyLoggedIn = -1176,
+    GarbledMessage = -1177,
+    BadSenderCompId = -1178,
+    BadSeqNum = -1179,
+    ExpectedLogon = -1180,
+    TooManyMessages = -1181,
+    ParametersBadCombination = -1182,
+    NotAllowedInDropCopySessions = -1183,
+    DropCopySessionNotAllowed = -1184,
+    DropCopySessionRequired = -1185,
+    NotAllowedInOrderEntrySessions = -1186,
+    NotAllowedInMarketDataSessions = -1187,
+    IncorrectNumInGroupCount = -1188,
+    DuplicateEntriesInAGroup = -1189,
+    InvalidRequestId = -1190,
+    TooManySubscriptions = -1191,
+    InvalidTimeUnit = -1194,
+    BuyOcoStopLossMustBeAbove = -1196,
+    SellOcoStopLossMustBeBelow = -1197,
+    BuyOcoTakeProfitMustBeBelow = -1198,
+    SellOcoTakeProfitMustBeAbove = -1199,
 
     // 20xx Processing issues
     BadApiId = -2008,
@@ -85,9 +142,30 @@ pub enum CommonCode {
     MinLeverageRatio = -2028,
 }
 
-impl Default for CommonCode {
-    fn default() -> Self {
-        Self::Unknown
+impl CommonCode {
+    pub fn kind(self) -> ErrorKind {
+        match self {
+            Self::Unauthorized
+            | Self::IpNotOnWhiteList
+            | Self::InvalidSignature
+            | Self::BadApiId
+            | Self::BadApiKeyFormat
+            | Self::RejectedApiKeyOrIp => ErrorKind::Auth,
+            Self::TooManyRequests
+            | Self::TooManyOrders
+            | Self::TooManyConnections
+            | Self::TooManyMessages => ErrorKind::RateLimited,
+            Self::BalanceNotSufficient | Self::MarginNotSufficient => {
+                ErrorKind::InsufficientBalance
+            }
+            Self::Unknown | Self::UnexpectedResponse | Self::Timeout => {
+                ErrorKind::UnknownOrderOutcome
+            }
+            Self::Disconnected | Self::ServerBusy | Self::ServiceShuttingDown => {
+                ErrorKind::Transient
+            }
+            _ => ErrorKind::InvalidRequest,
+        }
     }
 }
 
@@ -97,9 +175,15 @@ impl fmt::Display for CommonCode {
     }
 }
 
-#[derive(Clone, Copy, Debug, Deserialize_repr, FromPrimitive)]
+/// Codes of the USDⓈ-M Futures API, taken from its error code list, see
+/// <https://developers.binance.com/docs/derivatives/usds-margined-futures/error-code>. The table
+/// is maintained by hand and may lag behind the list. The COIN-M Futures API shares it, so only
+/// the COIN-M codes that USDⓈ-M also uses are listed; the others are reported as
+/// [`Code::Unknown`].
+#[derive(Clone, Copy, Debug, Default, Deserialize_repr, FromPrimitive)]
 #[repr(i16)]
 pub enum FApiCode {
+    #[default]
     Unknown = -1000,
     InvalidOrderStatus = -4000,
     PriceLessThanZero = -4001,
@@ -178,12 +262,52 @@ pub enum FApiCode {
     UpcomingMethod = -4084,
     InvalidNotionalLimitCoefficient = -4085,
     InvalidPriceSpreadThreshold = -4086,
-}
-
-impl Default for FApiCode {
-    fn default() -> Self {
-        Self::Unknown
-    }
+    ReduceOnlyOrderPermission = -4087,
+    NoPlaceOrderPermission = -4088,
+    InvalidContractType = -4104,
+    InvalidClientTransactionIdLength = -4114,
+    DuplicatedClientTransactionId = -4115,
+    ReduceOnlyMarginCheckFailed = -4118,
+    MarketOrderReject = -4131,
+    InvalidActivationPrice = -4135,
+    QuantityExistsWithClosePosition = -4137,
+    ReduceOnlyMustBeTrue = -4138,
+    OrderTypeCannotBeMarket = -4139,
+    InvalidOpeningPositionStatus = -4140,
+    SymbolAlreadyClosed = -4141,
+    StrategyInvalidTriggerPrice = -4142,
+    InvalidPair = -4144,
+    IsolatedLeverageRejectWithPosition = -4161,
+    MinNotional = -4164,
+    InvalidTimeInterval = -4165,
+    IsolatedRejectWithJointMargin = -4167,
+    JointMarginRejectWithIsolated = -4168,
+    JointMarginRejectWithMb = -4169,
+    JointMarginRejectWithOpenOrder = -4170,
+    NoNeedToChangeJointMargin = -4171,
+    JointMarginRejectWithNegativeBalance = -4172,
+    PriceHigherThanStopMultiplierUp = -4183,
+    PriceLowerThanStopMultiplierDown = -4184,
+    CoolingOffPeriod = -4192,
+    AdjustLeverageKycFailed = -4202,
+    AdjustLeverageOneMonthFailed = -4203,
+    AdjustLeverageXDaysFailed = -4205,
+    AdjustLeverageKycLimit = -4206,
+    AdjustLeverageAccountSymbolFailed = -4208,
+    AdjustLeverageSymbolFailed = -4209,
+    StopPriceHigherThanPriceMultiplierLimit = -4210,
+    StopPriceLowerThanPriceMultiplierLimit = -4211,
+    TradingQuantitativeRule = -4400,
+    ComplianceRestriction = -4401,
+    ComplianceBlackSymbolRestriction = -4402,
+    AdjustLeverageComplianceFailed = -4403,
+    FokOrderReject = -5021,
+    GtxOrderReject = -5022,
+    MoveOrderNotAllowedSymbolReason = -5024,
+    LimitOrderOnly = -5025,
+    ExceedMaximumModifyOrderLimit = -5026,
+    SameOrder = -5027,
+    MatchingEngineRecvWindowReject = -5028,
 }
 
 impl fmt::Display for FApiCode {
@@ -192,10 +316,38 @@ impl fmt::Display for FApiCode {
     }
 }
 
-#[derive(Clone, Copy, Debug, Deserialize_repr, FromPrimitive)]
+/// A selection of the codes of the Spot, Margin and Wallet SAPI endpoints, taken from the error
+/// code lists of the Margin Trading, Wallet, Simple Earn, BLVT and Portfolio Margin sections of
+/// <https://developers.binance.com/docs>. The ranges are not covered in full; missing codes are
+/// reported as [`Code::Unknown`].
+#[derive(Clone, Copy, Debug, Default, Deserialize_repr, FromPrimitive)]
 #[repr(i16)]
 pub enum SApiCode {
+    #[default]
     Unknown = -1000,
+
+    // 3xxx Margin issues
+    InnerFailure = -3000,
+    NeedEnable2fa = -3001,
+    AssetDeficiency = -3002,
+    NoOpenedMarginAccount = -3003,
+    TradeNotAllowed = -3004,
+    TransferOutNotAllowed = -3005,
+    ExceedMaxBorrowable = -3006,
+    HasPendingTransaction = -3007,
+    BorrowNotAllowed = -3008,
+    AssetNotMortgageable = -3009,
+    RepayNotAllowed = -3010,
+    BadDateRange = -3011,
+    AssetAdminBanBorrow = -3012,
+    LessThanMinBorrowable = -3013,
+    AccountBanBorrow = -3014,
+    RepayExceedLiability = -3015,
+    LessThanMinRepay = -3016,
+    AssetAdminBanMortgage = -3017,
+    AccountBanMortgage = -3018,
+    AccountBanRollout = -3019,
+    ExceedMaxRollout = -3020,
     PairAdminBanTrade = -3021,
     AccountBanTrade = -3022,
     WarningMarginLevel = -3023,
@@ -208,8 +360,14 @@ pub enum SApiCode {
     AccountBanRepay = -3036,
     ProfitAndLossClearing = -3037,
     ListenKeyNotFound = -3038,
+    BalanceNotCleared = -3041,
     PriceIndexNotFound = -3042,
+    TransferInNotAllowed = -3043,
+    SystemBusy = -3044,
+    SystemAssetNotEnough = -3045,
     NotWhitelistUser = -3999,
+
+    // 4xxx Wallet issues
     CapitalInvalid = -4001,
     CapitalInvalidGet = -4002,
     CapitalInvalidEmail = -4003,
@@ -218,7 +376,60 @@ pub enum SApiCode {
     CapitalOnlySupportPrimaryAccount = -4006,
     CapitalAddressVerificationNotPass = -4007,
     CapitalAddressTagVerificationNotPass = -4008,
+    CapitalWhitelistEmailConfirm = -4010,
+    CapitalWhitelistEmailExpired = -4011,
+    CapitalWhitelistClose = -4012,
+    CapitalWithdraw2faVerify = -4013,
+    CapitalWithdrawLoginDelay = -4014,
+    CapitalWithdrawRestrictedMinute = -4015,
+    CapitalWithdrawRestrictedPassword = -4016,
+    CapitalWithdrawRestrictedUnbind2fa = -4017,
+    CapitalWithdrawAssetNotExist = -4018,
+    CapitalWithdrawAssetProhibit = -4019,
+    CapitalWithdrawAmountMultiple = -4021,
+    CapitalWithdrawMinAmount = -4022,
+    CapitalWithdrawMaxAmount = -4023,
+    CapitalWithdrawUserNoAsset = -4024,
+    CapitalWithdrawUserAssetLessThanZero = -4025,
+    CapitalWithdrawUserAssetNotEnough = -4026,
+    CapitalWithdrawGetTransactionIdFailure = -4027,
+    CapitalWithdrawMoreThanFee = -4028,
+    CapitalWithdrawNotExist = -4029,
+    CapitalWithdrawConfirmSuccess = -4030,
+    CapitalWithdrawCancelFailure = -4031,
+    CapitalWithdrawChecksumVerifyFailure = -4032,
+    CapitalWithdrawIllegalAddress = -4033,
+    CapitalWithdrawAddressCheat = -4034,
+    CapitalWithdrawNotWhiteAddress = -4035,
+    CapitalWithdrawNewAddress = -4036,
+    CapitalWithdrawResendEmailFail = -4037,
+    CapitalWithdrawResendEmailTimeout = -4038,
+    CapitalUserEmpty = -4039,
+    CapitalNoCharge = -4040,
+    CapitalMinuteTooSmall = -4041,
+    CapitalChargeNotReset = -4042,
+    CapitalAddressTooMuch = -4043,
+    CapitalBlacklistCountryGetAddress = -4044,
+    CapitalGetAssetError = -4045,
+    CapitalAgreementNotConfirmed = -4046,
+    CapitalDateIntervalLimit = -4047,
+    CapitalWithdrawUserAssetLockDeposit = -4060,
+
+    // 5xxx Asset and transfer issues
+    AssetDribbletConvertSwitchOff = -5001,
+    AssetAssetNotEnough = -5002,
+    AssetUserHaveNoAsset = -5003,
+    UserOutOfTransferFloat = -5004,
+    UserAssetAmountIsTooLow = -5005,
+    UserCannotRequestIn24Hours = -5006,
+    AmountOverZero = -5007,
+    AssetWithdrawWithdrawingNotEnough = -5008,
+    ProductNotExist = -5009,
+    TransferFail = -5010,
     AssetNotSupported = -5011,
+    TransferPending = -5012,
+    ParentSubHaveNoRelation = -5021,
+    FutureAccountOrSubRelationNotExist = -5022,
 
     // 6xxx Savings issues
     DailyProductNotExists = -6001,
@@ -238,12 +449,30 @@ pub enum SApiCode {
     NotInWhitelist = -6017,
     AssetNotEnough = -6018,
     Pending = -6019,
-}
-
-impl Default for SApiCode {
-    fn default() -> Self {
-        Self::Unknown
-    }
+    ProjectNotExists = -6020,
+
+    // 7xxx Futures transfer issues
+    FuturesBadDateRange = -7001,
+    FuturesBadType = -7002,
+
+    // 13xxx BLVT issues
+    BlvtForbidRedeem = -13000,
+    BlvtExceedDailyLimit = -13001,
+    BlvtExceedTokenDailyLimit = -13002,
+    BlvtForbidPurchase = -13003,
+    BlvtExceedDailyPurchaseLimit = -13004,
+    BlvtExceedTokenDailyPurchaseLimit = -13005,
+    BlvtPurchaseLessThanMinAmount = -13006,
+    BlvtPurchaseAgreementNotSigned = -13007,
+
+    // 21xxx Portfolio margin issues
+    UserIsNotUnifiedAccount = -21001,
+    UnifiedAccountCannotTransferFuture = -21002,
+    NetAssetMustBeLessThanRatio = -21003,
+    UserNoLiability = -21004,
+    NoEnoughAsset = -21005,
+    HadInProcessRepay = -21006,
+    InForceLiquidation = -21007,
 }
 
 impl fmt::Display for SApiCode {
@@ -252,21 +481,24 @@ impl fmt::Display for SApiCode {
     }
 }
 
-#[derive(Clone, Copy, Debug, Deserialize_repr, FromPrimitive)]
+/// Codes of the replies to websocket stream requests like `SUBSCRIBE`, from the error messages of
+/// <https://developers.binance.com/docs/binance-spot-api-docs/web-socket-streams>. Codes in the
+/// REST ranges, e.g. `-1003`, are mapped to [`CommonCode`] as for the REST APIs, and any other
+/// code is reported as [`Code::Unknown`].
+#[derive(Clone, Copy, Debug, Default, Deserialize_repr, FromPrimitive)]
 #[repr(i16)]
 pub enum WSApiCode {
+    /// Unknown property, e.g. for `SET_PROPERTY`.
+    #[default]
     UnknownProperty = 0,
+    /// Invalid value type, e.g. `combined` is not a boolean.
     InvalidValueType = 1,
+    /// Invalid request, e.g. an unknown method, a non-integer `id` or too many parameters.
     InvalidRequest = 2,
+    /// The request is not valid JSON.
     InvalidJson = 3,
 }
 
-impl Default for WSApiCode {
-    fn default() -> Self {
-        Self::UnknownProperty
-    }
-}
-
 impl fmt::Display for WSApiCode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", *self as i16)
@@ -276,10 +508,40 @@ impl fmt::Display for WSApiCode {
 pub trait ApiCode:
     Clone + Copy + Default + fmt::Debug + fmt::Display + FromPrimitive + Send + Sync + 'static
 {
+    fn kind(self) -> ErrorKind {
+        ErrorKind::InvalidRequest
+    }
+}
+
+impl ApiCode for FApiCode {
+    fn kind(self) -> ErrorKind {
+        match self {
+            Self::CrossBalanceInsufficient | Self::IsolatedBalanceInsufficient => {
+                ErrorKind::InsufficientBalance
+            }
+            Self::InvalidApiKeyType | Self::InvalidRsaPublicKey => ErrorKind::Auth,
+            _ => ErrorKind::InvalidRequest,
+        }
+    }
+}
+
+impl ApiCode for SApiCode {
+    fn kind(self) -> ErrorKind {
+        match self {
+            Self::AssetDeficiency
+            | Self::CapitalWithdrawUserAssetNotEnough
+            | Self::AssetAssetNotEnough
+            | Self::BalanceNotEnough
+            | Self::AssetNotEnough
+            | Self::NoEnoughAsset => ErrorKind::InsufficientBalance,
+            Self::CapitalTooManyRequests | Self::RequestFrequencyTooHigh => ErrorKind::RateLimited,
+            Self::NeedEnable2fa | Self::CapitalUnauthenticated => ErrorKind::Auth,
+            Self::InnerFailure | Self::SystemBusy => ErrorKind::Transient,
+            _ => ErrorKind::InvalidRequest,
+        }
+    }
 }
 
-impl ApiCode for FApiCode {}
-impl ApiCode for SApiCode {}
 impl ApiCode for WSApiCode {}
 
 #[derive(Clone, Copy, Debug)]
@@ -287,34 +549,40 @@ pub enum Code<C: ApiCode> {
     Common(CommonCode),
     Api(C),
     Filter(i16),
+    /// A code that is missing from the code tables, which are hand-maintained subsets of the
+    /// codes Binance documents, e.g. one recently introduced by Binance.
+    Unknown(i16),
 }
 
-impl<C> TryFrom<i16> for Code<C>
+impl<C> From<i16> for Code<C>
 where
     C: ApiCode,
 {
-    type Error = &'static str;
-
-    fn try_from(value: i16) -> result::Result<Self, Self::Error> {
-        // Filter code range.
-        if value <= -9000 {
-            Ok(Self::Filter(value as i16))
-
-        // API-specific code range.
-        } else if value <= -3000 || value >= 0 {
-            if let Some(code) = FromPrimitive::from_i16(value as i16) {
-                Ok(Self::Api(code))
-            } else {
-                Err("a signed 16-bit integer")
-            }
+    fn from(value: i16) -> Self {
+        let known = match value {
+            // Filter code range.
+            -9999..=-9000 => Some(Self::Filter(value)),
+
+            // Common code range.
+            -2999..=-1000 => FromPrimitive::from_i16(value).map(Self::Common),
+
+            // API-specific code range.
+            _ => FromPrimitive::from_i16(value).map(Self::Api),
+        };
+        known.unwrap_or(Self::Unknown(value))
+    }
+}
 
-        // Common code range.
-        } else {
-            if let Some(code) = FromPrimitive::from_i16(value as i16) {
-                Ok(Self::Common(code))
-            } else {
-                Err("a signed 16-bit integer")
-            }
+impl<C> Code<C>
+where
+    C: ApiCode,
+{
+    pub fn kind(self) -> ErrorKind {
+        match self {
+            Self::Common(c) => c.kind(),
+            Self::Api(c) => c.kind(),
+            Self::Filter(_) => ErrorKind::InvalidRequest,
+            Self::Unknown(_) => ErrorKind::Other,
         }
     }
 }
@@ -362,15 +630,20 @@ where
             where
                 E: de::Error,
             {
-                let err =
-                    E::invalid_value(de::Unexpected::Signed(value), &"a signed 16-bit integer");
-
                 // Value must be within signed 16-bit integer range.
-                if value < i64::from(i16::MIN) || value > i64::from(i16::MAX) {
-                    Err(err)
-                } else {
-                    Self::Value::try_from(value as i16).map_err(|_| err)
-                }
+                i16::try_from(value).map(Self::Value::from).map_err(|_| {
+                    E::invalid_value(de::Unexpected::Signed(value), &"a signed 16-bit integer")
+                })
+            }
+
+            // Websocket codes are positive.
+            fn visit_u64<E>(self, value: u64) -> result::Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                i16::try_from(value).map(Self::Value::from).map_err(|_| {
+                    E::invalid_value(de::Unexpected::Unsigned(value), &"a signed 16-bit integer")
+                })
             }
         }
 
@@ -387,6 +660,7 @@ where
             Self::Common(c) => fmt::Display::fmt(c, f),
             Self::Api(c) => fmt::Display::fmt(c, f),
             Self::Filter(c) => fmt::Display::fmt(c, f),
+            Self::Unknown(c) => fmt::Display::fmt(c, f),
         }
     }
 }
@@ -409,6 +683,101 @@ where
     pub fn msg(&self) -> &str {
         &self.msg
     }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.code.kind()
+    }
+}
+
+/// A coarse classification of an [`Error`], e.g. to decide how to react to it.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ErrorKind {
+    /// A request weight, order count or firewall limit has been reached.
+    RateLimited,
+    /// The IP address has been banned for exceeding the rate limits.
+    Banned,
+    /// The API key, signature or IP address has been rejected.
+    Auth,
+    /// The request has been rejected, e.g. because of an unknown symbol or a filter failure.
+    InvalidRequest,
+    /// The account lacks the balance or margin to execute the request.
+    InsufficientBalance,
+    /// The request may or may not have been executed, e.g. an order may have been placed.
+    UnknownOrderOutcome,
+    /// The request has not been executed because of a network or server issue.
+    Transient,
+    /// Any other error, such as a response that could not be decoded.
+    Other,
+}
+
+impl ErrorKind {
+    /// Whether the request can be sent again as is, possibly after a delay.
+    pub fn is_retryable(self) -> bool {
+        matches!(self, Self::RateLimited | Self::Transient)
+    }
+}
+
+/// The REST request that an [`Error`] occurred in.
+#[derive(Clone, Debug)]
+pub struct ErrorContext {
+    pub method: Method,
+    /// The path of the endpoint, without the query string.
+    pub path: String,
+    /// The HTTP status of the response, unless no response has been received.
+    pub status: Option<StatusCode>,
+    /// The raw body of a response that could not be decoded.
+    pub body: Option<String>,
+}
+
+impl<C> From<BoxError> for Error<C>
+where
+    C: ApiCode,
+{
+    fn from(e: BoxError) -> Self {
+        match e.downcast::<reqwest::Error>() {
+            Ok(e) => Self::HttpRequest(*e),
+            Err(e) => Self::Transport(e),
+        }
+    }
+}
+
+/// The error of a [`Market::klines_range`](crate::market::Market::klines_range) stream.
+#[derive(Debug, thiserror::Error)]
+pub enum KlinesRangeError<C: ApiCode> {
+    /// Klines between two consecutive klines of the range are missing, e.g. while trading was
+    /// halted.
+    #[error("Missing klines from {from} to {to}")]
+    Gap { from: Time, to: Time },
+
+    #[error(transparent)]
+    Request(#[from] Error<C>),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CredentialsError {
+    #[error("Invalid private key: {0}")]
+    InvalidPrivateKey(String),
+
+    #[error("Invalid key file: {0}")]
+    InvalidKeyFile(String),
+
+    #[error("Missing secret: {0}")]
+    MissingSecret(String),
+
+    #[error("Secret provider failed: {0}")]
+    Provider(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConnectionError {
+    #[error("HTTP client cannot be built: {0}")]
+    HttpClient(#[source] reqwest::Error),
+
+    #[error("Invalid certificate: {0}")]
+    InvalidCertificate(String),
+
+    #[error("Invalid proxy: {0}")]
+    InvalidProxy(String),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -420,19 +789,19 @@ pub enum Error<C: ApiCode> {
     BadRequest(#[source] BinanceError<C>),
 
     #[error("Firewall limit reached")]
-    FirewallLimitReached,
+    FirewallLimitReached { retry_after: Option<Duration> },
 
     #[error("HTTP request error: {0}")]
     HttpRequest(#[from] reqwest::Error),
 
     #[error("IP address has been banned")]
-    IPAddressBanned,
+    IPAddressBanned { retry_after: Option<Duration> },
 
     #[error("Request encoding error: {0}")]
     RequestEncoding(#[from] serde_urlencoded::ser::Error),
 
     #[error("Request rate limit reached")]
-    RequestRateLimitReached,
+    RequestRateLimitReached { retry_after: Option<Duration> },
 
     #[error("Response decoding error: {0}")]
     ResponseDecoding(#[from] serde_json::Error),
@@ -440,6 +809,21 @@ pub enum Error<C: ApiCode> {
     #[error("Internal server error: {0}")]
     Server(#[source] BinanceError<C>),
 
+    #[error("Request signing error: {0}")]
+    Signing(#[source] BoxError),
+
+    #[error("HTTP transport error: {0}")]
+    Transport(#[source] BoxError),
+
+    /// An error response without a Binance error in its body, e.g. an HTML page of a proxy.
+    /// The body is truncated.
+    #[error("Unexpected HTTP {status} response: {body}")]
+    UnexpectedResponse {
+        status: StatusCode,
+        headers: HeaderMap,
+        body: String,
+    },
+
     #[error("Websocket error: {0}")]
     Websocket(#[from] async_tungstenite::tungstenite::Error),
 
@@ -455,3 +839,173 @@ pub enum Error<C: ApiCode> {
     #[error("Websocket request timed out")]
     WebsocketRequestTimeout,
 }
+
+impl<C> Error<C>
+where
+    C: ApiCode,
+{
+    /// Classifies the error. Without the method of the failed request, timeouts and server errors
+    /// are assumed to have an unknown outcome; [`Error::kind_for`] and [`ContextError::kind`]
+    /// tell apart the requests that cannot have placed, changed or cancelled an order.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::ApiTimeout => ErrorKind::UnknownOrderOutcome,
+            Self::BadRequest(e) => e.kind(),
+            Self::FirewallLimitReached { .. } => ErrorKind::RateLimited,
+            // A request that timed out after it was sent may have been executed.
+            Self::HttpRequest(e) if e.is_timeout() && !e.is_connect() => {
+                ErrorKind::UnknownOrderOutcome
+            }
+            Self::HttpRequest(_) => ErrorKind::Transient,
+            Self::IPAddressBanned { .. } => ErrorKind::Banned,
+            Self::RequestEncoding(_) => ErrorKind::InvalidRequest,
+            Self::RequestRateLimitReached { .. } => ErrorKind::RateLimited,
+            Self::ResponseDecoding(_) => ErrorKind::Other,
+            Self::Server(e) => match e.kind() {
+                ErrorKind::Transient => ErrorKind::Transient,
+                _ => ErrorKind::UnknownOrderOutcome,
+            },
+            Self::Signing(_) => ErrorKind::Auth,
+            Self::Transport(_) => ErrorKind::Transient,
+            Self::UnexpectedResponse { status, .. } if status.is_server_error() => {
+                ErrorKind::UnknownOrderOutcome
+            }
+            Self::UnexpectedResponse { .. } => ErrorKind::InvalidRequest,
+            Self::Websocket(_) => ErrorKind::Transient,
+            Self::WebsocketClosed => ErrorKind::Transient,
+            Self::WebsocketRequest(e) => e.kind(),
+            Self::WebsocketRequestCancelled => ErrorKind::Transient,
+            Self::WebsocketRequestTimeout => ErrorKind::Transient,
+        }
+    }
+
+    /// Classifies the error of a request sent with `method`. Timeouts and server errors of GET
+    /// requests are transient, since only POST, PUT and DELETE requests change orders.
+    pub fn kind_for(&self, method: &Method) -> ErrorKind {
+        match self.kind() {
+            ErrorKind::UnknownOrderOutcome if *method == Method::GET => ErrorKind::Transient,
+            kind => kind,
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        self.kind().is_retryable()
+    }
+
+    pub(crate) fn with_context(self, context: ErrorContext) -> ContextError<C> {
+        ContextError {
+            context,
+            error: self,
+        }
+    }
+}
+
+/// An [`Error`] along with the REST request that it occurred in, returned by providers in the
+/// [`WithMetadata`](crate::response::WithMetadata) mode.
+#[derive(Debug, thiserror::Error)]
+#[error("{} {}: {error}", context.method, context.path)]
+pub struct ContextError<C: ApiCode> {
+    pub context: ErrorContext,
+    #[source]
+    pub error: Error<C>,
+}
+
+impl<C> ContextError<C>
+where
+    C: ApiCode,
+{
+    pub fn kind(&self) -> ErrorKind {
+        self.error.kind_for(&self.context.method)
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        self.kind().is_retryable()
+    }
+}
+
+impl<C> From<ContextError<C>> for Error<C>
+where
+    C: ApiCode,
+{
+    fn from(e: ContextError<C>) -> Self {
+        e.error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kind() {
+        let code = |c: i16| Code::<FApiCode>::from(c).kind();
+        assert_eq!(code(-1022), ErrorKind::Auth);
+        assert_eq!(code(-1003), ErrorKind::RateLimited);
+        assert_eq!(code(-1007), ErrorKind::UnknownOrderOutcome);
+        assert_eq!(code(-1121), ErrorKind::InvalidRequest);
+        assert_eq!(code(-4050), ErrorKind::InsufficientBalance);
+        assert_eq!(code(-9000), ErrorKind::InvalidRequest);
+
+        let server =
+            |c: i16| Error::<FApiCode>::Server(BinanceError::new(Code::from(c), String::new()));
+        assert_eq!(server(-1001).kind(), ErrorKind::Transient);
+        assert!(server(-1001).is_retryable());
+        assert_eq!(server(-1000).kind(), ErrorKind::UnknownOrderOutcome);
+        assert!(!server(-1000).is_retryable());
+
+        let context = |method: Method| ErrorContext {
+            method,
+            path: "/fapi/v1/order".to_string(),
+            status: Some(StatusCode::INTERNAL_SERVER_ERROR),
+            body: None,
+        };
+        let post = server(-1000).with_context(context(Method::POST));
+        assert_eq!(post.kind(), ErrorKind::UnknownOrderOutcome);
+        assert!(!post.is_retryable());
+        let delete = Error::<FApiCode>::ApiTimeout.with_context(context(Method::DELETE));
+        assert_eq!(delete.kind(), ErrorKind::UnknownOrderOutcome);
+        let get = server(-1000).with_context(context(Method::GET));
+        assert_eq!(get.kind(), ErrorKind::Transient);
+        assert!(get.is_retryable());
+        let get = Error::<FApiCode>::ApiTimeout.with_context(context(Method::GET));
+        assert_eq!(get.kind(), ErrorKind::Transient);
+        assert_eq!(server(-1001).kind_for(&Method::POST), ErrorKind::Transient);
+
+        assert_eq!(Code::<FApiCode>::from(-1234).kind(), ErrorKind::Other);
+
+        let banned = Error::<FApiCode>::IPAddressBanned { retry_after: None };
+        assert_eq!(banned.kind(), ErrorKind::Banned);
+        assert!(!banned.is_retryable());
+    }
+
+    #[test]
+    fn unknown_code() {
+        let e: BinanceError<FApiCode> =
+            serde_json::from_str(r#"{"code":-4999,"msg":"Something new."}"#).unwrap();
+        assert!(matches!(e.code(), Code::Unknown(-4999)));
+        assert_eq!(e.msg(), "Something new.");
+        assert_eq!(e.to_string(), "(-4999) Something new.");
+
+        let e: BinanceError<SApiCode> =
+            serde_json::from_str(r#"{"code":-13000,"msg":"Redemption is forbidden."}"#).unwrap();
+        assert!(matches!(e.code(), Code::Api(SApiCode::BlvtForbidRedeem)));
+
+        let e: BinanceError<WSApiCode> =
+            serde_json::from_str(r#"{"code":2,"msg":"Invalid request"}"#).unwrap();
+        assert!(matches!(e.code(), Code::Api(WSApiCode::InvalidRequest)));
+
+        let e: BinanceError<WSApiCode> =
+            serde_json::from_str(r#"{"code":-1003,"msg":"Too many requests."}"#).unwrap();
+        assert!(matches!(
+            e.code(),
+            Code::Common(CommonCode::TooManyRequests)
+        ));
+
+        let e: BinanceError<WSApiCode> =
+            serde_json::from_str(r#"{"code":4,"msg":"Something new."}"#).unwrap();
+        assert!(matches!(e.code(), Code::Unknown(4)));
+
+        assert!(matches!(Code::<FApiCode>::from(-9001), Code::Filter(-9001)));
+        assert!(serde_json::from_str::<Code<FApiCode>>("40000").is_err());
+    }
+}
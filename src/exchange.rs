@@ -1,30 +1,65 @@
-use derive_more::Constructor;
+use std::marker::PhantomData;
+
 use serde::{de::DeserializeOwned, Deserialize};
 
 use crate::{
-    client::{Api, Client, FApi, SApi},
-    error::Result,
+    client::{Api, Client, DApi, FApi, SApi},
     models::*,
+    response::{ModeResult, Plain, ResponseMode, WithMetadata},
 };
 
-#[derive(Clone, Constructor, Debug)]
-pub struct Exchange<A: Api + ExchangeApi> {
+#[derive(Clone, Debug)]
+pub struct Exchange<A: Api + ExchangeApi, M = Plain> {
     client: Client<A>,
+    _mode: PhantomData<M>,
 }
 
 impl<A> Exchange<A>
 where
     A: Api + ExchangeApi,
 {
-    pub async fn info(&self) -> Result<ExchangeInfo<A::OrderType, A::SymbolDetails>, A::ErrorCode> {
-        self.client.get(A::info(), Empty::new()).await
+    pub fn new(client: Client<A>) -> Self {
+        Self {
+            client,
+            _mode: PhantomData,
+        }
+    }
+
+    /// Makes every call return a [`Response`](crate::response::Response) carrying the metadata
+    /// of the HTTP response, such as the used request weight, or a
+    /// [`ContextError`](crate::error::ContextError) carrying the request that failed.
+    pub fn with_metadata(self) -> Exchange<A, WithMetadata> {
+        Exchange {
+            client: self.client,
+            _mode: PhantomData,
+        }
+    }
+}
+
+impl<A, M> Exchange<A, M>
+where
+    A: Api + ExchangeApi,
+    M: ResponseMode,
+{
+    pub async fn info(
+        &self,
+    ) -> ModeResult<M, ExchangeInfo<A::OrderType, A::SymbolDetails>, A::ErrorCode> {
+        self.client
+            .get(A::info(), Empty::new())
+            .await
+            .map_err(M::error)
+            .map(M::output)
     }
 
-    pub async fn ping(&self) -> Result<Empty, A::ErrorCode> {
-        self.client.get(A::ping(), Empty::new()).await
+    pub async fn ping(&self) -> ModeResult<M, Empty, A::ErrorCode> {
+        self.client
+            .get(A::ping(), Empty::new())
+            .await
+            .map_err(M::error)
+            .map(M::output)
     }
 
-    pub async fn time(&self) -> Result<Time, A::ErrorCode> {
+    pub async fn time(&self) -> ModeResult<M, Time, A::ErrorCode> {
         #[derive(Deserialize)]
         #[serde(rename_all = "camelCase")]
         struct ServerTime {
@@ -34,7 +69,8 @@ where
         self.client
             .get::<_, ServerTime>(A::time(), Empty::new())
             .await
-            .map(|st| st.server_time)
+            .map_err(M::error)
+            .map(|r| M::output(r.map(|st| st.server_time)))
     }
 }
 
@@ -44,7 +80,19 @@ pub trait ExchangeApi {
 
     fn info() -> &'static str;
     fn ping() -> &'static str;
-    fn time() -> &'static str;
+}
+
+impl ExchangeApi for DApi {
+    type OrderType = FOrderType;
+    type SymbolDetails = DSymbol;
+
+    fn info() -> &'static str {
+        "/dapi/v1/exchangeInfo"
+    }
+
+    fn ping() -> &'static str {
+        "/dapi/v1/ping"
+    }
 }
 
 impl ExchangeApi for FApi {
@@ -58,10 +106,6 @@ impl ExchangeApi for FApi {
     fn ping() -> &'static str {
         "/fapi/v1/ping"
     }
-
-    fn time() -> &'static str {
-        "/fapi/v1/time"
-    }
 }
 
 impl ExchangeApi for SApi {
@@ -75,18 +119,48 @@ impl ExchangeApi for SApi {
     fn ping() -> &'static str {
         "/api/v3/ping"
     }
-
-    fn time() -> &'static str {
-        "/api/v3/time"
-    }
 }
 
 #[cfg(test)]
 mod tests {
+    use rust_decimal::Decimal;
+
     use super::*;
+    use crate::vcr::replay_client;
 
     #[tokio::test]
     async fn info() {
-        eprintln!("{:?}", Exchange::new(Client::<FApi>::new()).info().await);
+        let client = replay_client::<FApi>("fapi_exchange_info.json");
+        let info = Exchange::new(client).info().await.unwrap();
+        assert_eq!(info.rate_limits.len(), 3);
+        assert_eq!(info.symbols.len(), 1);
+        assert_eq!(info.symbols[0].symbol, "BTCUSDT");
+        assert_eq!(info.symbols[0].details.quantity_precision, 3);
+    }
+
+    #[tokio::test]
+    async fn coin_m_info() {
+        let client = replay_client::<DApi>("dapi_exchange_info.json");
+        let info = Exchange::new(client).info().await.unwrap();
+        let quarterly = &info.symbols[1];
+        assert_eq!(quarterly.symbol, "BTCUSD_220624");
+        assert_eq!(
+            quarterly.details.contract_type,
+            ContractType::CurrentQuarter
+        );
+        assert_eq!(quarterly.details.contract_size, Decimal::from(100));
+        assert_eq!(
+            quarterly.details.delivery_date.to_rfc3339(),
+            "2022-06-24T08:00:00+00:00"
+        );
+    }
+
+    #[tokio::test]
+    async fn info_with_metadata() {
+        let client = replay_client::<FApi>("fapi_exchange_info.json");
+        let resp = Exchange::new(client).with_metadata().info().await.unwrap();
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.used_weight_1m(), Some(1));
+        assert_eq!(resp.data.symbols.len(), 1);
     }
 }
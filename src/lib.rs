@@ -1,10 +1,22 @@
 pub mod account;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod client;
+pub mod clock;
+pub mod connection;
+pub mod credentials;
 pub mod error;
 pub mod exchange;
 pub mod extensions;
 pub mod market;
 pub mod models;
+pub mod ratelimit;
+pub mod response;
+pub mod retry;
+mod rt;
 pub mod serde;
+pub mod signer;
 pub mod trade;
+pub mod transport;
+pub mod vcr;
 pub mod ws;
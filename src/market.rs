@@ -1,49 +1,908 @@
-use derive_more::Constructor;
+use std::cmp;
+use std::iter;
+use std::marker::PhantomData;
+use std::result;
+
+use chrono::{DateTime, Months, Utc};
+use futures::{
+    future,
+    stream::{self, Stream, StreamExt, TryStreamExt},
+};
+use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{
-    client::{Api, Client, FApi, SApi},
-    error::Result,
+    client::{Api, Client, DApi, FApi, SApi},
+    error::{Error, KlinesRangeError, Result},
     models::*,
+    response::{ModeResult, Plain, ResponseMode, WithMetadata},
 };
 
-#[derive(Clone, Constructor, Debug)]
-pub struct Market<A: Api + MarketApi> {
+// The page sizes of paginated streams whose request sets no limit.
+const AGG_TRADES_PAGE_LIMIT: usize = 1000;
+const FUNDING_RATE_PAGE_LIMIT: usize = 1000;
+const KLINES_PAGE_LIMIT: usize = 1000;
+const TRADES_PAGE_LIMIT: usize = 500;
+
+#[derive(Clone, Debug)]
+pub struct Market<A: Api + MarketApi, M = Plain> {
     client: Client<A>,
+    _mode: PhantomData<M>,
 }
 
 impl<A> Market<A>
 where
     A: Api + MarketApi,
+{
+    pub fn new(client: Client<A>) -> Self {
+        Self {
+            client,
+            _mode: PhantomData,
+        }
+    }
+
+    /// Makes every call return a [`Response`](crate::response::Response) carrying the metadata
+    /// of the HTTP response, such as the used request weight, or a
+    /// [`ContextError`](crate::error::ContextError) carrying the request that failed.
+    pub fn with_metadata(self) -> Market<A, WithMetadata> {
+        Market {
+            client: self.client,
+            _mode: PhantomData,
+        }
+    }
+}
+
+impl<A, M> Market<A, M>
+where
+    A: Api + MarketApi,
+    M: ResponseMode,
 {
     pub async fn agg_trades<S>(
         &self,
         req: AggTradesRequest<S>,
-    ) -> Result<Vec<AggTradesRecord>, A::ErrorCode>
+    ) -> ModeResult<M, Vec<AggTradesRecord>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.client
+            .get(A::agg_trades(), req)
+            .await
+            .map_err(M::error)
+            .map(M::output)
+    }
+
+    /// Pages forward through the aggregated trades from the `from_id` of `req`, or from the first
+    /// trade at or after its `start_time`, or from the latest trades. Stops at `until`, or at the
+    /// `end_time` of `req` if that comes first, or at the latest trade. The `limit` of `req` sets
+    /// the page size.
+    ///
+    /// Binance only looks for trades in the hour after a `startTime`, so the first trade is
+    /// searched for an hour at a time, which takes a request per hour without trades.
+    pub fn agg_trades_range<S>(
+        &self,
+        req: AggTradesRequest<S>,
+        until: Option<TradesBound>,
+    ) -> impl Stream<Item = Result<AggTradesRecord, A::ErrorCode>> + '_
+    where
+        S: AsRef<str>,
+    {
+        struct Cursor {
+            // The ID of the next trade to yield, or the latest trade.
+            next: Option<u64>,
+            // The time to find the next trade from, before the first page.
+            start_time: Option<Time>,
+            done: bool,
+        }
+
+        let symbol = req.symbol.as_ref().to_string();
+        let limit = req.limit.unwrap_or(AGG_TRADES_PAGE_LIMIT).max(1);
+        let end_time = req.end_time;
+        let until_time = match until {
+            Some(TradesBound::Time(time)) => Some(time),
+            _ => None,
+        };
+        let is_passed = move |t: &AggTradesRecord| {
+            until.is_some_and(|until| until.is_passed_at(t.id, t.time, Direction::Forward))
+                || end_time.is_some_and(|end_time| t.time > end_time)
+        };
+        let cursor = Cursor {
+            next: req.from_id,
+            start_time: req.start_time.filter(|_| req.from_id.is_none()),
+            done: false,
+        };
+
+        stream::try_unfold(cursor, move |mut cursor| {
+            let symbol = symbol.clone();
+            async move {
+                if cursor.done {
+                    return Ok::<_, Error<A::ErrorCode>>(None);
+                }
+
+                if let Some(mut start_time) = cursor.start_time.take() {
+                    let last_time = end_time
+                        .into_iter()
+                        .chain(until_time)
+                        .min()
+                        .unwrap_or_else(|| Utc::now().into());
+                    loop {
+                        if start_time > last_time {
+                            return Ok(None);
+                        }
+                        let window_end = *start_time + chrono::Duration::hours(1);
+                        let req = AggTradesRequest {
+                            symbol: symbol.as_str(),
+                            from_id: None,
+                            start_time: Some(start_time),
+                            end_time: Some(cmp::min(
+                                (window_end - chrono::Duration::milliseconds(1)).into(),
+                                last_time,
+                            )),
+                            limit: Some(1),
+                        };
+                        let first: Vec<AggTradesRecord> =
+                            self.client.get(A::agg_trades(), req).await?.into_inner();
+                        if let Some(first) = first.first() {
+                            cursor.next = Some(first.id);
+                            break;
+                        }
+                        start_time = window_end.into();
+                    }
+                }
+
+                let req = AggTradesRequest {
+                    symbol,
+                    from_id: cursor.next,
+                    start_time: None,
+                    end_time: None,
+                    limit: Some(limit),
+                };
+                let mut page: Vec<AggTradesRecord> =
+                    self.client.get(A::agg_trades(), req).await?.into_inner();
+                let full = page.len() == limit;
+
+                let passed = page.iter().position(is_passed);
+                if let Some(i) = passed {
+                    page.truncate(i);
+                }
+
+                let next = match page.last() {
+                    Some(last) if full => Some(last.id + 1),
+                    _ => None,
+                };
+                let cursor = Cursor {
+                    done: passed.is_some() || next.is_none(),
+                    next,
+                    start_time: None,
+                };
+
+                Ok(Some((stream::iter(page.into_iter().map(Ok)), cursor)))
+            }
+        })
+        .try_flatten()
+    }
+
+    /// Trades older than the recent trades. Requires an API key.
+    pub async fn historical_trades<S>(
+        &self,
+        req: HistoricalTradesRequest<S>,
+    ) -> ModeResult<M, Vec<TradesRecord>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.client
+            .get(A::historical_trades(), req)
+            .await
+            .map_err(M::error)
+            .map(M::output)
+    }
+
+    /// Pages through the historical trades, starting at the `from_id` of `req` (or the latest
+    /// trade) and walking in `direction` until `until` is reached or no trades are left. The
+    /// `limit` of `req` sets the page size. Requires an API key.
+    pub fn historical_trades_stream<S>(
+        &self,
+        req: HistoricalTradesRequest<S>,
+        direction: Direction,
+        until: Option<TradesBound>,
+    ) -> impl Stream<Item = Result<TradesRecord, A::ErrorCode>> + '_
+    where
+        S: AsRef<str>,
+    {
+        struct Cursor {
+            // The ID of the next trade to yield, or the latest trade.
+            next: Option<u64>,
+            done: bool,
+        }
+
+        let symbol = req.symbol.as_ref().to_string();
+        let limit = req.limit.unwrap_or(TRADES_PAGE_LIMIT).max(1);
+        let cursor = Cursor {
+            next: req.from_id,
+            done: false,
+        };
+
+        stream::try_unfold(cursor, move |cursor| {
+            let symbol = symbol.clone();
+            async move {
+                if cursor.done {
+                    return Ok::<_, Error<A::ErrorCode>>(None);
+                }
+
+                let from_id = match direction {
+                    Direction::Forward => cursor.next,
+                    Direction::Backward => cursor
+                        .next
+                        .map(|next| next.saturating_sub(limit as u64 - 1)),
+                };
+                let req = HistoricalTradesRequest {
+                    symbol,
+                    limit: Some(limit),
+                    from_id,
+                };
+                let mut page: Vec<TradesRecord> = self
+                    .client
+                    .get(A::historical_trades(), req)
+                    .await?
+                    .into_inner();
+                let full = page.len() == limit;
+
+                if direction == Direction::Backward {
+                    if let Some(next) = cursor.next {
+                        page.retain(|t| t.id <= next);
+                    }
+                    page.reverse();
+                }
+
+                let passed = page
+                    .iter()
+                    .position(|t| until.is_some_and(|until| until.is_passed_by(t, direction)));
+                if let Some(i) = passed {
+                    page.truncate(i);
+                }
+
+                let next = match (direction, page.last()) {
+                    (Direction::Forward, Some(last)) if full => Some(last.id + 1),
+                    (Direction::Backward, Some(last)) if last.id > 0 => Some(last.id - 1),
+                    _ => None,
+                };
+                let cursor = Cursor {
+                    done: passed.is_some() || next.is_none(),
+                    next,
+                };
+
+                Ok(Some((stream::iter(page.into_iter().map(Ok)), cursor)))
+            }
+        })
+        .try_flatten()
+    }
+
+    pub async fn klines<S>(
+        &self,
+        req: KlinesRequest<S>,
+    ) -> ModeResult<M, Vec<KlinesRecord>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.client
+            .get(A::klines(), req)
+            .await
+            .map_err(M::error)
+            .map(M::output)
+    }
+
+    /// Streams the klines from the `start_time` of `req` (or the first kline) to its `end_time`
+    /// (or now), splitting the range into pages of `limit` klines. Klines that pages overlap on
+    /// are yielded once, and missing klines are reported as set in `options`. The stream ends
+    /// after its first error, so the kline following a gap is not yielded when failing on gaps.
+    pub fn klines_range<S>(
+        &self,
+        req: KlinesRequest<S>,
+        options: KlinesRangeOptions,
+    ) -> impl Stream<Item = result::Result<KlinesRecord, KlinesRangeError<A::ErrorCode>>> + '_
+    where
+        S: AsRef<str>,
+    {
+        let symbol = req.symbol.as_ref().to_string();
+        let interval = req.interval;
+        let limit = req.limit.unwrap_or(KLINES_PAGE_LIMIT).max(1);
+        let end = req.end_time.map_or_else(Utc::now, |t| *t);
+
+        let start_time = req.start_time;
+        let first_symbol = symbol.clone();
+        let start = async move {
+            if let Some(start_time) = start_time {
+                return Ok::<_, KlinesRangeError<A::ErrorCode>>(Some(*start_time));
+            }
+
+            let req = KlinesRequest {
+                symbol: first_symbol,
+                interval,
+                start_time: Some(Time::default()),
+                end_time: None,
+                limit: Some(1),
+            };
+            let first: Vec<KlinesRecord> = self
+                .client
+                .get(A::klines(), req)
+                .await
+                .map_err(Error::from)?
+                .into_inner();
+            Ok(first.first().map(|k| *k.open_time))
+        };
+
+        let mut last: Option<(Time, Time)> = None;
+        stream::once(start)
+            .map_ok(move |start| {
+                let symbol = symbol.clone();
+                let pages = start
+                    .into_iter()
+                    .flat_map(move |start| kline_pages(interval, limit, start, end));
+                stream::iter(pages)
+                    .map(move |(start, end)| {
+                        let req = KlinesRequest {
+                            symbol: symbol.clone(),
+                            interval,
+                            start_time: Some(start.into()),
+                            end_time: Some(end.into()),
+                            limit: Some(limit),
+                        };
+                        async move {
+                            self.client
+                                .get::<_, Vec<KlinesRecord>>(A::klines(), req)
+                                .await
+                                .map_err(|e| KlinesRangeError::Request(e.into()))
+                                .map(|r| stream::iter(r.into_inner().into_iter().map(Ok)))
+                        }
+                    })
+                    .buffered(options.concurrency.max(1))
+            })
+            .try_flatten()
+            .try_flatten()
+            .try_filter_map(move |kline| {
+                let result = match last {
+                    Some((open_time, _)) if kline.open_time <= open_time => Ok(None),
+                    Some((_, close_time))
+                        if *kline.open_time > *close_time + chrono::Duration::milliseconds(1) =>
+                    {
+                        let (from, to) = (close_time, kline.open_time);
+                        last = Some((kline.open_time, kline.close_time));
+                        if options.fail_on_gaps {
+                            Err(KlinesRangeError::Gap { from, to })
+                        } else {
+                            tracing::warn!(%from, %to, "missing klines");
+                            Ok(Some(kline))
+                        }
+                    }
+                    _ => {
+                        last = Some((kline.open_time, kline.close_time));
+                        Ok(Some(kline))
+                    }
+                };
+                future::ready(result)
+            })
+            .scan(false, |failed, result| {
+                let result = (!*failed).then_some(result);
+                *failed = matches!(result, Some(Err(_)));
+                future::ready(result)
+            })
+    }
+
+    pub async fn order_book<S>(
+        &self,
+        req: OrderBookRequest<S>,
+    ) -> ModeResult<M, OrderBook, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.client
+            .get(A::order_book(), req)
+            .await
+            .map_err(M::error)
+            .map(M::output)
+    }
+
+    pub async fn book_ticker<S>(
+        &self,
+        req: TickerRequest<S>,
+    ) -> ModeResult<M, Vec<BookTicker>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.tickers(A::book_ticker(), req, |t: &BookTicker| &t.symbol)
+            .await
+    }
+
+    pub async fn recent_trades<S>(
+        &self,
+        req: TradesRequest<S>,
+    ) -> ModeResult<M, Vec<TradesRecord>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.client
+            .get(A::trades(), req)
+            .await
+            .map_err(M::error)
+            .map(M::output)
+    }
+
+    pub async fn ticker_24hr<S>(
+        &self,
+        req: TickerRequest<S>,
+    ) -> ModeResult<M, Vec<Ticker24hr>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.tickers(A::ticker_24hr(), req, |t: &Ticker24hr| &t.symbol)
+            .await
+    }
+
+    pub async fn ticker_price<S>(
+        &self,
+        req: TickerRequest<S>,
+    ) -> ModeResult<M, Vec<TickerPrice>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.tickers(A::ticker_price(), req, |t: &TickerPrice| &t.symbol)
+            .await
+    }
+
+    async fn tickers<S, T>(
+        &self,
+        path: &'static str,
+        req: TickerRequest<S>,
+        symbol: fn(&T) -> &str,
+    ) -> ModeResult<M, Vec<T>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+        T: DeserializeOwned,
+    {
+        // Without support for a list of symbols, all tickers are requested and filtered.
+        let (req, filter) = match req.symbols {
+            Some(symbols) if !A::ticker_symbols_list() => (
+                TickerRequest {
+                    symbol: None,
+                    symbols: None,
+                },
+                Some(symbols),
+            ),
+            symbols => (
+                TickerRequest {
+                    symbol: req.symbol,
+                    symbols,
+                },
+                None,
+            ),
+        };
+
+        self.client
+            .get::<_, OneOrMany<T>>(path, req)
+            .await
+            .map_err(M::error)
+            .map(|r| {
+                M::output(r.map(|tickers| {
+                    let mut tickers = Vec::from(tickers);
+                    if let Some(symbols) = filter {
+                        tickers.retain(|t| symbols.iter().any(|s| s.as_ref() == symbol(t)));
+                    }
+                    tickers
+                }))
+            })
+    }
+}
+
+impl<A, M> Market<A, M>
+where
+    A: Api + FuturesMarketApi,
+    M: ResponseMode,
+{
+    pub async fn continuous_klines<S>(
+        &self,
+        req: ContinuousKlinesRequest<S>,
+    ) -> ModeResult<M, Vec<KlinesRecord>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.client
+            .get(A::continuous_klines(), req)
+            .await
+            .map_err(M::error)
+            .map(M::output)
+    }
+
+    pub async fn funding_rate_history<S>(
+        &self,
+        req: FundingRateRequest<S>,
+    ) -> ModeResult<M, Vec<FundingRate>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.client
+            .get(A::funding_rate(), req)
+            .await
+            .map_err(M::error)
+            .map(M::output)
+    }
+
+    /// Pages forward through the funding rates from the `start_time` of `req` (or the first
+    /// funding rate) to its `end_time` (or the latest funding rate). The `limit` of `req` sets
+    /// the page size.
+    pub fn funding_rate_history_stream<S>(
+        &self,
+        req: FundingRateRequest<S>,
+    ) -> impl Stream<Item = Result<FundingRate, A::ErrorCode>> + '_
+    where
+        S: AsRef<str>,
+    {
+        let symbol = req.symbol.as_ref().to_string();
+        let limit = req.limit.unwrap_or(FUNDING_RATE_PAGE_LIMIT).max(1);
+        let end_time = req.end_time;
+
+        // The start time of the next page, until the funding rates run out.
+        stream::try_unfold(
+            Some(req.start_time.unwrap_or_default()),
+            move |start_time| {
+                let symbol = symbol.clone();
+                async move {
+                    let start_time = match start_time {
+                        Some(start_time) => start_time,
+                        None => return Ok::<_, Error<A::ErrorCode>>(None),
+                    };
+
+                    let req = FundingRateRequest {
+                        symbol,
+                        start_time: Some(start_time),
+                        end_time,
+                        limit: Some(limit),
+                    };
+                    let page: Vec<FundingRate> =
+                        self.client.get(A::funding_rate(), req).await?.into_inner();
+                    let next = match page.last() {
+                        Some(last) if page.len() == limit => Some(Time::from(
+                            *last.funding_time + chrono::Duration::milliseconds(1),
+                        )),
+                        _ => None,
+                    };
+
+                    Ok(Some((stream::iter(page.into_iter().map(Ok)), next)))
+                }
+            },
+        )
+        .try_flatten()
+    }
+
+    pub async fn global_long_short_account_ratio<S>(
+        &self,
+        req: FuturesDataRequest<S>,
+    ) -> ModeResult<M, Vec<LongShortRatio>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.client
+            .get(A::global_long_short_account_ratio(), req)
+            .await
+            .map_err(M::error)
+            .map(M::output)
+    }
+
+    /// The klines of the index price of a pair, which is given as the symbol of `req`.
+    pub async fn index_price_klines<S>(
+        &self,
+        req: KlinesRequest<S>,
+    ) -> ModeResult<M, Vec<KlinesRecord>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct IndexPriceKlinesRequest<'a> {
+            pair: &'a str,
+            interval: ChartInterval,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            start_time: Option<Time>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            end_time: Option<Time>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            limit: Option<usize>,
+        }
+
+        let req = IndexPriceKlinesRequest {
+            pair: req.symbol.as_ref(),
+            interval: req.interval,
+            start_time: req.start_time,
+            end_time: req.end_time,
+            limit: req.limit,
+        };
+        self.client
+            .get(A::index_price_klines(), req)
+            .await
+            .map_err(M::error)
+            .map(M::output)
+    }
+
+    pub async fn mark_price_klines<S>(
+        &self,
+        req: KlinesRequest<S>,
+    ) -> ModeResult<M, Vec<KlinesRecord>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.client
+            .get(A::mark_price_klines(), req)
+            .await
+            .map_err(M::error)
+            .map(M::output)
+    }
+
+    pub async fn open_interest<S>(&self, symbol: S) -> ModeResult<M, OpenInterest, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.client
+            .get(A::open_interest(), [("symbol", symbol.as_ref())])
+            .await
+            .map_err(M::error)
+            .map(M::output)
+    }
+
+    pub async fn open_interest_history<S>(
+        &self,
+        req: FuturesDataRequest<S>,
+    ) -> ModeResult<M, Vec<OpenInterestHist>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.client
+            .get(A::open_interest_history(), req)
+            .await
+            .map_err(M::error)
+            .map(M::output)
+    }
+
+    /// The premium index of one symbol, or of all symbols, as selected by `req`.
+    pub async fn premium_index<S>(
+        &self,
+        req: SymbolRequest<S>,
+    ) -> ModeResult<M, Vec<PremiumIndex>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.client
+            .get::<_, OneOrMany<PremiumIndex>>(A::premium_index(), req)
+            .await
+            .map_err(M::error)
+            .map(|r| M::output(r.map(Vec::from)))
+    }
+
+    pub async fn premium_index_klines<S>(
+        &self,
+        req: KlinesRequest<S>,
+    ) -> ModeResult<M, Vec<KlinesRecord>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.client
+            .get(A::premium_index_klines(), req)
+            .await
+            .map_err(M::error)
+            .map(M::output)
+    }
+
+    pub async fn taker_buy_sell_volume<S>(
+        &self,
+        req: FuturesDataRequest<S>,
+    ) -> ModeResult<M, Vec<TakerVolume>, A::ErrorCode>
     where
         S: AsRef<str>,
     {
-        self.client.get(A::agg_trades(), req).await
+        self.client
+            .get(A::taker_buy_sell_volume(), req)
+            .await
+            .map_err(M::error)
+            .map(M::output)
     }
 
-    pub async fn klines<S>(&self, req: KlinesRequest<S>) -> Result<Vec<KlinesRecord>, A::ErrorCode>
+    pub async fn top_long_short_account_ratio<S>(
+        &self,
+        req: FuturesDataRequest<S>,
+    ) -> ModeResult<M, Vec<LongShortRatio>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.client
+            .get(A::top_long_short_account_ratio(), req)
+            .await
+            .map_err(M::error)
+            .map(M::output)
+    }
+
+    pub async fn top_long_short_position_ratio<S>(
+        &self,
+        req: FuturesDataRequest<S>,
+    ) -> ModeResult<M, Vec<LongShortRatio>, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.client
+            .get(A::top_long_short_position_ratio(), req)
+            .await
+            .map_err(M::error)
+            .map(M::output)
+    }
+}
+
+impl<A, M> Market<A, M>
+where
+    A: Api + SpotMarketApi,
+    M: ResponseMode,
+{
+    /// The average price that the `PercentPrice` filter of the Spot API is checked against.
+    pub async fn avg_price<S>(&self, symbol: S) -> ModeResult<M, AvgPrice, A::ErrorCode>
+    where
+        S: AsRef<str>,
+    {
+        self.client
+            .get(A::avg_price(), [("symbol", symbol.as_ref())])
+            .await
+            .map_err(M::error)
+            .map(M::output)
+    }
+
+    /// The statistics over the last `window_size`, which defaults to a day. Requires a symbol or
+    /// a list of symbols.
+    pub async fn rolling_window_ticker<S>(
+        &self,
+        req: TickerRequest<S>,
+        window_size: Option<WindowSize>,
+    ) -> ModeResult<M, Vec<WindowTicker>, A::ErrorCode>
     where
         S: AsRef<str>,
     {
-        self.client.get(A::klines(), req).await
+        let req = WindowTickerRequest {
+            tickers: req,
+            window_size,
+            time_zone: None,
+        };
+        self.client
+            .get::<_, OneOrMany<WindowTicker>>(A::rolling_window_ticker(), req)
+            .await
+            .map_err(M::error)
+            .map(|r| M::output(r.map(Vec::from)))
     }
 
-    pub async fn order_book<S>(&self, req: OrderBookRequest<S>) -> Result<OrderBook, A::ErrorCode>
+    /// The statistics of the current trading day in `time_zone` (e.g. `"-1:00"` or `"05:45"`),
+    /// which defaults to UTC. Requires a symbol or a list of symbols.
+    pub async fn trading_day_ticker<S>(
+        &self,
+        req: TickerRequest<S>,
+        time_zone: Option<&str>,
+    ) -> ModeResult<M, Vec<WindowTicker>, A::ErrorCode>
     where
         S: AsRef<str>,
     {
-        self.client.get(A::order_book(), req).await
+        let req = WindowTickerRequest {
+            tickers: req,
+            window_size: None,
+            time_zone,
+        };
+        self.client
+            .get::<_, OneOrMany<WindowTicker>>(A::trading_day_ticker(), req)
+            .await
+            .map_err(M::error)
+            .map(|r| M::output(r.map(Vec::from)))
     }
 }
 
+// The bounds of the pages of `limit` klines from `start` to `end`. Months vary in length, so they
+// are paged by calendar.
+fn kline_pages(
+    interval: ChartInterval,
+    limit: usize,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> impl Iterator<Item = (DateTime<Utc>, DateTime<Utc>)> {
+    let next = move |start: &DateTime<Utc>| match interval {
+        ChartInterval::OneMonth => start.checked_add_months(Months::new(limit as u32)),
+        _ => start.checked_add_signed(interval.duration() * limit as i32),
+    };
+
+    iter::successors(Some(start), next)
+        .take_while(move |start| *start <= end)
+        .map(move |start| {
+            let page_end = next(&start).map_or(end, |next| {
+                cmp::min(next - chrono::Duration::milliseconds(1), end)
+            });
+            (start, page_end)
+        })
+}
+
 pub trait MarketApi {
     fn agg_trades() -> &'static str;
+    fn historical_trades() -> &'static str;
     fn klines() -> &'static str;
     fn order_book() -> &'static str;
+    fn book_ticker() -> &'static str;
+    fn ticker_24hr() -> &'static str;
+    fn ticker_price() -> &'static str;
+    fn trades() -> &'static str;
+
+    /// Whether the ticker endpoints accept a list of symbols.
+    fn ticker_symbols_list() -> bool {
+        false
+    }
+}
+
+/// Implemented by the APIs with futures derivatives market data, i.e. the USDⓈ-M Futures API.
+///
+/// ```compile_fail
+/// use binance_api::{client::SClient, market::Market};
+///
+/// # async fn open_interest() {
+/// let _ = Market::new(SClient::new()).open_interest("BTCUSDT").await;
+/// # }
+/// ```
+pub trait FuturesMarketApi: MarketApi {
+    fn continuous_klines() -> &'static str;
+    fn funding_rate() -> &'static str;
+    fn global_long_short_account_ratio() -> &'static str;
+    fn index_price_klines() -> &'static str;
+    fn mark_price_klines() -> &'static str;
+    fn open_interest() -> &'static str;
+    fn open_interest_history() -> &'static str;
+    fn premium_index() -> &'static str;
+    fn premium_index_klines() -> &'static str;
+    fn taker_buy_sell_volume() -> &'static str;
+    fn top_long_short_account_ratio() -> &'static str;
+    fn top_long_short_position_ratio() -> &'static str;
+}
+
+/// Implemented by the APIs with spot market data, i.e. the Spot API.
+///
+/// ```compile_fail
+/// use binance_api::{client::FClient, market::Market};
+///
+/// # async fn avg_price() {
+/// let _ = Market::new(FClient::new()).avg_price("BTCUSDT").await;
+/// # }
+/// ```
+pub trait SpotMarketApi: MarketApi {
+    fn avg_price() -> &'static str;
+    fn rolling_window_ticker() -> &'static str;
+    fn trading_day_ticker() -> &'static str;
+}
+
+impl MarketApi for DApi {
+    fn agg_trades() -> &'static str {
+        "/dapi/v1/aggTrades"
+    }
+
+    fn historical_trades() -> &'static str {
+        "/dapi/v1/historicalTrades"
+    }
+
+    fn klines() -> &'static str {
+        "/dapi/v1/klines"
+    }
+
+    fn order_book() -> &'static str {
+        "/dapi/v1/depth"
+    }
+
+    fn book_ticker() -> &'static str {
+        "/dapi/v1/ticker/bookTicker"
+    }
+
+    fn ticker_24hr() -> &'static str {
+        "/dapi/v1/ticker/24hr"
+    }
+
+    fn ticker_price() -> &'static str {
+        "/dapi/v1/ticker/price"
+    }
+
+    fn trades() -> &'static str {
+        "/dapi/v1/trades"
+    }
 }
 
 impl MarketApi for FApi {
@@ -51,6 +910,10 @@ impl MarketApi for FApi {
         "/fapi/v1/aggTrades"
     }
 
+    fn historical_trades() -> &'static str {
+        "/fapi/v1/historicalTrades"
+    }
+
     fn klines() -> &'static str {
         "/fapi/v1/klines"
     }
@@ -58,6 +921,22 @@ impl MarketApi for FApi {
     fn order_book() -> &'static str {
         "/fapi/v1/depth"
     }
+
+    fn book_ticker() -> &'static str {
+        "/fapi/v1/ticker/bookTicker"
+    }
+
+    fn ticker_24hr() -> &'static str {
+        "/fapi/v1/ticker/24hr"
+    }
+
+    fn ticker_price() -> &'static str {
+        "/fapi/v1/ticker/price"
+    }
+
+    fn trades() -> &'static str {
+        "/fapi/v1/trades"
+    }
 }
 
 impl MarketApi for SApi {
@@ -65,6 +944,10 @@ impl MarketApi for SApi {
         "/api/v3/aggTrades"
     }
 
+    fn historical_trades() -> &'static str {
+        "/api/v3/historicalTrades"
+    }
+
     fn klines() -> &'static str {
         "/api/v3/klines"
     }
@@ -72,56 +955,424 @@ impl MarketApi for SApi {
     fn order_book() -> &'static str {
         "/api/v3/depth"
     }
+
+    fn book_ticker() -> &'static str {
+        "/api/v3/ticker/bookTicker"
+    }
+
+    fn ticker_24hr() -> &'static str {
+        "/api/v3/ticker/24hr"
+    }
+
+    fn ticker_price() -> &'static str {
+        "/api/v3/ticker/price"
+    }
+
+    fn trades() -> &'static str {
+        "/api/v3/trades"
+    }
+
+    fn ticker_symbols_list() -> bool {
+        true
+    }
+}
+
+impl FuturesMarketApi for FApi {
+    fn continuous_klines() -> &'static str {
+        "/fapi/v1/continuousKlines"
+    }
+
+    fn funding_rate() -> &'static str {
+        "/fapi/v1/fundingRate"
+    }
+
+    fn global_long_short_account_ratio() -> &'static str {
+        "/futures/data/globalLongShortAccountRatio"
+    }
+
+    fn index_price_klines() -> &'static str {
+        "/fapi/v1/indexPriceKlines"
+    }
+
+    fn mark_price_klines() -> &'static str {
+        "/fapi/v1/markPriceKlines"
+    }
+
+    fn open_interest() -> &'static str {
+        "/fapi/v1/openInterest"
+    }
+
+    fn open_interest_history() -> &'static str {
+        "/futures/data/openInterestHist"
+    }
+
+    fn premium_index() -> &'static str {
+        "/fapi/v1/premiumIndex"
+    }
+
+    fn premium_index_klines() -> &'static str {
+        "/fapi/v1/premiumIndexKlines"
+    }
+
+    fn taker_buy_sell_volume() -> &'static str {
+        "/futures/data/takerlongshortRatio"
+    }
+
+    fn top_long_short_account_ratio() -> &'static str {
+        "/futures/data/topLongShortAccountRatio"
+    }
+
+    fn top_long_short_position_ratio() -> &'static str {
+        "/futures/data/topLongShortPositionRatio"
+    }
+}
+
+impl SpotMarketApi for SApi {
+    fn avg_price() -> &'static str {
+        "/api/v3/avgPrice"
+    }
+
+    fn rolling_window_ticker() -> &'static str {
+        "/api/v3/ticker"
+    }
+
+    fn trading_day_ticker() -> &'static str {
+        "/api/v3/ticker/tradingDay"
+    }
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//     use crate::Exchange;
-
-//     #[tokio::test]
-//     async fn agg_trades() {
-//         let client = Client::<FApi>::new(None);
-//         let exchange = Exchange::new(client.clone());
-//         let market = Market::new(client);
-
-//         let info = exchange.info().await.unwrap();
-//         let btc = info.symbols.iter().find(|s| s.symbol == "BTCUSDT").unwrap();
-//         let trades = market.agg_trades(AggTradesRequest::new(btc)).await.unwrap();
-
-//         eprintln!("{:?}", trades);
-//     }
-
-//     #[tokio::test]
-//     async fn klines() {
-//         let client = Client::<FApi>::new(None);
-//         let exchange = Exchange::new(client.clone());
-//         let market = Market::new(client);
-
-//         let info = exchange.info().await.unwrap();
-//         let btc = info.symbols.iter().find(|s| s.symbol == "BTCUSDT").unwrap();
-//         let klines = market
-//             .klines(
-//                 KlinesRequest::new(btc, ChartInterval::OneMinute)
-//                     .start_time("2020-06-01T00:00:00Z")
-//                     .end_time("2020-06-01T07:59:59Z"),
-//             )
-//             .await
-//             .unwrap();
-
-//         eprintln!("{:?}", klines);
-//     }
-
-//     #[tokio::test]
-//     async fn order_book() {
-//         let client = Client::<SApi>::new(None);
-//         let exchange = Exchange::new(client.clone());
-//         let market = Market::new(client);
-
-//         let info = exchange.info().await.unwrap();
-//         let btc = info.symbols.iter().find(|s| s.symbol == "BTCUSDT").unwrap();
-//         let order_book = market.order_book(OrderBookRequest::new(btc)).await.unwrap();
-
-//         eprintln!("{:?}", order_book);
-//     }
-// }
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+    use std::time::Duration;
+
+    use chrono::prelude::*;
+
+    use super::*;
+    use crate::vcr::replay_client;
+
+    #[tokio::test]
+    async fn futures_klines() {
+        let market = Market::new(replay_client::<FApi>("fapi_klines.json"));
+
+        let klines = market
+            .continuous_klines(
+                ContinuousKlinesRequest::new(
+                    "BTCUSDT",
+                    ContractType::CurrentQuarter,
+                    ChartInterval::OneHour,
+                )
+                .limit(2),
+            )
+            .await
+            .unwrap();
+        assert_eq!(klines.len(), 2);
+        assert_eq!(klines[1].num_trades, 6128);
+
+        let klines = market
+            .index_price_klines(KlinesRequest::new("BTCUSDT", ChartInterval::OneHour).limit(1))
+            .await
+            .unwrap();
+        assert_eq!(klines[0].close, "29682.53185714".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn futures_data() {
+        let market = Market::new(replay_client::<FApi>("fapi_futures_data.json"));
+
+        let index = market
+            .premium_index(SymbolRequest::single("BTCUSDT"))
+            .await
+            .unwrap();
+        assert_eq!(index[0].mark_price, "29663.9".parse().unwrap());
+
+        let indexes = market.premium_index(SymbolRequest::all()).await.unwrap();
+        assert_eq!(indexes.len(), 2);
+        assert_eq!(indexes[1].symbol, "ETHUSDT");
+
+        let open_interest = market.open_interest("BTCUSDT").await.unwrap();
+        assert_eq!(open_interest.open_interest, "92310.784".parse().unwrap());
+
+        let ratios = market
+            .top_long_short_position_ratio(
+                FuturesDataRequest::new("BTCUSDT", DataPeriod::OneHour).limit(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(ratios[0].long_short_ratio, "1.2201".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn funding_rate_history_stream() {
+        let market = Market::new(replay_client::<FApi>("fapi_futures_data.json"));
+
+        let rates: Vec<_> = market
+            .funding_rate_history_stream(
+                FundingRateRequest::new("BTCUSDT")
+                    .start_time(Utc.timestamp_millis_opt(1653955200000).unwrap())
+                    .end_time(Utc.timestamp_millis_opt(1654128000000).unwrap())
+                    .limit(2),
+            )
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(rates.len(), 3);
+        assert!(rates[0].mark_price.is_none());
+        assert_eq!(rates[2].funding_rate, "-0.00002381".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn recent_trades() {
+        let market = Market::new(replay_client::<FApi>("fapi_recent_trades.json"));
+
+        let trades = market
+            .recent_trades(TradesRequest::new("BTCUSDT").limit(2))
+            .await
+            .unwrap();
+        let ids: Vec<_> = trades.iter().map(|t| t.id).collect();
+        assert_eq!(ids, [2302583702, 2302583703]);
+        assert_eq!(trades[1].price, "29665.00".parse().unwrap());
+        assert!(trades[1].buyer_is_maker);
+        assert_eq!(trades[1].best_match, None);
+    }
+
+    #[tokio::test]
+    async fn historical_trades_forward() {
+        let market = Market::new(replay_client::<FApi>("fapi_historical_trades.json"));
+
+        let trades: Vec<_> = market
+            .historical_trades_stream(
+                HistoricalTradesRequest::new("BTCUSDT")
+                    .from_id(2302583700)
+                    .limit(2),
+                Direction::Forward,
+                Some(TradesBound::Id(2302583702)),
+            )
+            .try_collect()
+            .await
+            .unwrap();
+        let ids: Vec<_> = trades.iter().map(|t| t.id).collect();
+        assert_eq!(ids, [2302583700, 2302583701, 2302583702]);
+    }
+
+    #[tokio::test]
+    async fn historical_trades_backward() {
+        let market = Market::new(replay_client::<FApi>("fapi_historical_trades.json"));
+
+        let until = Time::from(Utc.timestamp_millis_opt(1654128000250).unwrap());
+        let trades: Vec<_> = market
+            .historical_trades_stream(
+                HistoricalTradesRequest::new("BTCUSDT")
+                    .from_id(2302583703)
+                    .limit(2),
+                Direction::Backward,
+                Some(TradesBound::Time(until)),
+            )
+            .try_collect()
+            .await
+            .unwrap();
+        let ids: Vec<_> = trades.iter().map(|t| t.id).collect();
+        assert_eq!(ids, [2302583703, 2302583702, 2302583701]);
+    }
+
+    #[tokio::test]
+    async fn agg_trades_range() {
+        let market = Market::new(replay_client::<FApi>("fapi_agg_trades_range.json"));
+
+        // Nothing traded in the first hour, so the first trade is found in the second one.
+        let trades: Vec<_> = market
+            .agg_trades_range(
+                AggTradesRequest::new("BTCUSDT")
+                    .start_time("2022-06-01T00:00:00Z")
+                    .limit(2),
+                Some(TradesBound::Id(1200000502)),
+            )
+            .try_collect()
+            .await
+            .unwrap();
+        let ids: Vec<_> = trades.iter().map(|t| t.id).collect();
+        assert_eq!(ids, [1200000500, 1200000501, 1200000502]);
+
+        let trades: Vec<_> = market
+            .agg_trades_range(
+                AggTradesRequest::new("BTCUSDT")
+                    .start_time("2022-06-02T00:00:00Z")
+                    .end_time("2022-06-02T01:30:00Z"),
+                None,
+            )
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(trades.is_empty());
+    }
+
+    #[tokio::test]
+    async fn avg_price() {
+        let market = Market::new(replay_client::<SApi>("sapi_ticker.json"));
+
+        let avg = market.avg_price("BTCUSDT").await.unwrap();
+        assert_eq!(avg.mins, 5);
+        assert_eq!(avg.price, "29671.43251046".parse().unwrap());
+    }
+
+    fn klines_range_request() -> KlinesRequest<&'static str> {
+        KlinesRequest::new("BTCUSDT", ChartInterval::OneHour)
+            .start_time("2022-06-01T00:00:00Z")
+            .end_time("2022-06-01T04:59:59.999Z")
+            .limit(2)
+    }
+
+    #[tokio::test]
+    async fn klines_range() {
+        let market = Market::new(replay_client::<FApi>("fapi_klines_range.json"));
+
+        let klines: Vec<_> = market
+            .klines_range(klines_range_request(), KlinesRangeOptions::new())
+            .try_collect()
+            .await
+            .unwrap();
+        let trades: Vec<_> = klines.iter().map(|k| k.num_trades).collect();
+        assert_eq!(trades, [7000, 7001, 7003, 7004]);
+    }
+
+    #[tokio::test]
+    async fn klines_range_from_first_kline() {
+        let market = Market::new(replay_client::<FApi>("fapi_klines_range.json"));
+
+        let req = KlinesRequest::new("BTCUSDT", ChartInterval::OneHour)
+            .end_time("2022-06-01T04:59:59.999Z")
+            .limit(2);
+        let klines: Vec<_> = market
+            .klines_range(req, KlinesRangeOptions::new())
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(klines[0].open_time.timestamp_millis(), 1654041600000);
+        assert_eq!(klines.len(), 4);
+    }
+
+    #[test]
+    fn kline_pages_by_month() {
+        let start = Utc.with_ymd_and_hms(2021, 1, 15, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+
+        let pages: Vec<_> = kline_pages(ChartInterval::OneMonth, 12, start, end).collect();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(
+            pages[0].1,
+            Utc.with_ymd_and_hms(2022, 1, 14, 23, 59, 59).unwrap()
+                + chrono::Duration::milliseconds(999)
+        );
+        assert_eq!(
+            pages[1],
+            (Utc.with_ymd_and_hms(2022, 1, 15, 0, 0, 0).unwrap(), end)
+        );
+    }
+
+    #[tokio::test]
+    async fn klines_range_gap() {
+        let market = Market::new(replay_client::<FApi>("fapi_klines_range.json"));
+
+        let results: Vec<_> = market
+            .klines_range(
+                klines_range_request(),
+                KlinesRangeOptions::new().concurrency(3).fail_on_gaps(true),
+            )
+            .collect()
+            .await;
+        assert_eq!(results.len(), 3);
+        assert!(results[..2].iter().all(|r| r.is_ok()));
+        match &results[2] {
+            Err(KlinesRangeError::Gap { from, to }) => {
+                assert_eq!(from.timestamp_millis(), 1654048799999);
+                assert_eq!(to.timestamp_millis(), 1654052400000);
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[tokio::test]
+    async fn order_book() {
+        let market = Market::new(replay_client::<SApi>("sapi_order_book.json"));
+
+        let order_book = market
+            .order_book(OrderBookRequest::new("BTCUSDT").limit(5))
+            .await
+            .unwrap();
+        assert_eq!(order_book.last_update_id, 27089417215);
+        assert_eq!(order_book.bids.len(), 3);
+        assert_eq!(order_book.bids[0].0, "29665.09".parse().unwrap());
+        assert_eq!(order_book.asks[0].1, "0.38316".parse().unwrap());
+        assert!(order_book.transaction_time.is_none());
+    }
+
+    #[tokio::test]
+    async fn rolling_window_ticker() {
+        let market = Market::new(replay_client::<SApi>("sapi_ticker.json"));
+
+        let window = WindowSize::try_from(Duration::from_secs(4 * 3600)).unwrap();
+        assert_eq!(window, WindowSize::hours(4).unwrap());
+        let tickers = market
+            .rolling_window_ticker(
+                TickerRequest::list(vec!["BTCUSDT", "ETHUSDT"]),
+                Some(window),
+            )
+            .await
+            .unwrap();
+        assert_eq!(tickers.len(), 2);
+        assert_eq!(tickers[1].num_trades, 212776);
+    }
+
+    #[test]
+    fn window_size() {
+        assert_eq!(WindowSize::minutes(59).unwrap().to_string(), "59m");
+        assert!(WindowSize::minutes(60).is_none());
+        assert!(WindowSize::hours(24).is_none());
+        assert!(WindowSize::days(8).is_none());
+        assert_eq!(
+            WindowSize::try_from(Duration::from_secs(2 * 86400)),
+            Ok(WindowSize::days(2).unwrap())
+        );
+        assert!(WindowSize::try_from(Duration::from_secs(90)).is_err());
+    }
+
+    #[tokio::test]
+    async fn ticker_24hr() {
+        let market = Market::new(replay_client::<FApi>("fapi_ticker.json"));
+
+        let tickers = market
+            .ticker_24hr(TickerRequest::single("BTCUSDT"))
+            .await
+            .unwrap();
+        assert_eq!(tickers.len(), 1);
+        assert_eq!(tickers[0].symbol, "BTCUSDT");
+        assert_eq!(tickers[0].last_price, "29665.10".parse().unwrap());
+        assert_eq!(tickers[0].num_trades, 2572698);
+    }
+
+    #[tokio::test]
+    async fn ticker_price_filters_symbols() {
+        let market = Market::new(replay_client::<FApi>("fapi_ticker.json"));
+
+        let prices = market
+            .ticker_price(TickerRequest::list(vec!["ETHUSDT", "XRPUSDT"]))
+            .await
+            .unwrap();
+        let symbols: Vec<_> = prices.iter().map(|p| p.symbol.as_str()).collect();
+        assert_eq!(symbols, ["ETHUSDT", "XRPUSDT"]);
+        assert!(prices[0].time.is_some());
+    }
+
+    #[tokio::test]
+    async fn book_ticker() {
+        let market = Market::new(replay_client::<SApi>("sapi_ticker.json"));
+
+        let tickers = market
+            .book_ticker(TickerRequest::list(vec!["BTCUSDT", "ETHUSDT"]))
+            .await
+            .unwrap();
+        assert_eq!(tickers.len(), 2);
+        assert_eq!(tickers[1].best_ask_qty, "3.0556".parse().unwrap());
+        assert!(tickers[1].update_id.is_none());
+    }
+}
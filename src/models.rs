@@ -1,6 +1,7 @@
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::str::FromStr;
+use std::time::Duration;
 
 use chrono::{prelude::*, serde::ts_milliseconds};
 use derive_more::{Constructor, Deref, DerefMut, Display, From};
@@ -9,7 +10,6 @@ use serde::{
     de::{self, DeserializeOwned},
     Deserialize, Serialize,
 };
-use tokio::time::Duration;
 
 use crate::error::{ApiCode, BinanceError, Error, WSApiCode};
 
@@ -160,6 +160,16 @@ where
     pub countdown_time: u64,
 }
 
+/// The average price of a symbol over the last `mins` minutes.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvgPrice {
+    pub mins: u32,
+    pub price: Decimal,
+    #[serde(default)]
+    pub close_time: Option<Time>,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Balance {
@@ -170,6 +180,7 @@ pub struct Balance {
     #[serde(rename = "crossUnPnl")]
     pub cross_unrealized_profit: Decimal,
     pub available_balance: Decimal,
+    #[serde(alias = "withdrawAvailable")]
     pub max_withdraw_amount: Decimal,
 }
 
@@ -180,6 +191,50 @@ pub enum BatchOrder<Details, Type, C: ApiCode> {
     Error(BinanceError<C>),
 }
 
+/// The best bid and ask of a symbol, with the fields of [`WSEventBookTicker`].
+#[derive(Clone, Debug, Deserialize)]
+pub struct BookTicker {
+    pub symbol: String,
+    // Only present in Futures API.
+    #[serde(default, rename = "lastUpdateId")]
+    pub update_id: Option<u64>,
+    // Only present in Futures API.
+    #[serde(default, rename = "time")]
+    pub transaction_time: Option<Time>,
+    #[serde(rename = "bidPrice")]
+    pub best_bid_price: Decimal,
+    #[serde(rename = "bidQty")]
+    pub best_bid_qty: Decimal,
+    #[serde(rename = "askPrice")]
+    pub best_ask_price: Decimal,
+    #[serde(rename = "askQty")]
+    pub best_ask_qty: Decimal,
+}
+
+/// Converts a [`WSEventDetails::BookTicker`] event, or returns any other event as is.
+impl<OrderType> TryFrom<WSEvent<OrderType>> for BookTicker {
+    type Error = WSEvent<OrderType>;
+
+    fn try_from(event: WSEvent<OrderType>) -> Result<Self, WSEvent<OrderType>> {
+        match event {
+            WSEvent {
+                symbol: Some(symbol),
+                details: WSEventDetails::BookTicker(t),
+                ..
+            } => Ok(Self {
+                symbol,
+                update_id: t.update_id,
+                transaction_time: t.transaction_time,
+                best_bid_price: t.best_bid_price,
+                best_bid_qty: t.best_bid_qty,
+                best_ask_price: t.best_ask_price,
+                best_ask_qty: t.best_ask_qty,
+            }),
+            event => Err(event),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Display, Serialize)]
 pub enum ChartInterval {
     #[display(fmt = "1m")]
@@ -243,12 +298,158 @@ pub enum ChartInterval {
     OneMonth,
 }
 
+impl ChartInterval {
+    /// The length of a kline. Months are taken to be 31 days long, their longest.
+    pub fn duration(&self) -> chrono::Duration {
+        match self {
+            Self::OneMinute => chrono::Duration::minutes(1),
+            Self::ThreeMinute => chrono::Duration::minutes(3),
+            Self::FiveMinute => chrono::Duration::minutes(5),
+            Self::FifteenMinute => chrono::Duration::minutes(15),
+            Self::ThirtyMinute => chrono::Duration::minutes(30),
+            Self::OneHour => chrono::Duration::hours(1),
+            Self::TwoHour => chrono::Duration::hours(2),
+            Self::FourHour => chrono::Duration::hours(4),
+            Self::SixHour => chrono::Duration::hours(6),
+            Self::EightHour => chrono::Duration::hours(8),
+            Self::TwelveHour => chrono::Duration::hours(12),
+            Self::OneDay => chrono::Duration::days(1),
+            Self::ThreeDay => chrono::Duration::days(3),
+            Self::OneWeek => chrono::Duration::weeks(1),
+            Self::OneMonth => chrono::Duration::days(31),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ContingencyType {
     OCO,
 }
 
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContinuousKlinesRequest<S>
+where
+    S: AsRef<str>,
+{
+    #[serde(serialize_with = "crate::serde::serialize_as_ref")]
+    pub pair: S,
+    pub contract_type: ContractType,
+    pub interval: ChartInterval,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<Time>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<Time>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+impl<S> ContinuousKlinesRequest<S>
+where
+    S: AsRef<str>,
+{
+    pub fn new(pair: S, contract_type: ContractType, interval: ChartInterval) -> Self {
+        Self {
+            pair,
+            contract_type,
+            interval,
+            start_time: None,
+            end_time: None,
+            limit: None,
+        }
+    }
+
+    pub fn start_time<T>(mut self, start_time: T) -> Self
+    where
+        T: TryInto<Time>,
+    {
+        self.start_time = start_time.try_into().ok();
+        self
+    }
+
+    pub fn end_time<T>(mut self, end_time: T) -> Self
+    where
+        T: TryInto<Time>,
+    {
+        self.end_time = end_time.try_into().ok();
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ContractType {
+    #[display(fmt = "PERPETUAL")]
+    Perpetual,
+    #[display(fmt = "CURRENT_MONTH")]
+    CurrentMonth,
+    #[display(fmt = "NEXT_MONTH")]
+    NextMonth,
+    #[display(fmt = "CURRENT_QUARTER")]
+    CurrentQuarter,
+    #[display(fmt = "NEXT_QUARTER")]
+    NextQuarter,
+    #[display(fmt = "PERPETUAL_DELIVERING")]
+    PerpetualDelivering,
+    #[display(fmt = "CURRENT_QUARTER_DELIVERING")]
+    CurrentQuarterDelivering,
+    #[display(fmt = "NEXT_QUARTER_DELIVERING")]
+    NextQuarterDelivering,
+}
+
+/// The period of the statistics of the `/futures/data` endpoints.
+#[derive(Clone, Copy, Debug, Display, Serialize)]
+pub enum DataPeriod {
+    #[display(fmt = "5m")]
+    #[serde(rename = "5m")]
+    FiveMinute,
+
+    #[display(fmt = "15m")]
+    #[serde(rename = "15m")]
+    FifteenMinute,
+
+    #[display(fmt = "30m")]
+    #[serde(rename = "30m")]
+    ThirtyMinute,
+
+    #[display(fmt = "1h")]
+    #[serde(rename = "1h")]
+    OneHour,
+
+    #[display(fmt = "2h")]
+    #[serde(rename = "2h")]
+    TwoHour,
+
+    #[display(fmt = "4h")]
+    #[serde(rename = "4h")]
+    FourHour,
+
+    #[display(fmt = "6h")]
+    #[serde(rename = "6h")]
+    SixHour,
+
+    #[display(fmt = "12h")]
+    #[serde(rename = "12h")]
+    TwelveHour,
+
+    #[display(fmt = "1d")]
+    #[serde(rename = "1d")]
+    OneDay,
+}
+
+/// The order in which a paginated stream walks through its records.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Direction {
+    Forward,
+    Backward,
+}
+
 #[derive(Clone, Constructor, Copy, Debug, Deserialize, Serialize)]
 pub struct Empty {}
 
@@ -276,6 +477,162 @@ pub struct ExchangeInfo<OrderType, SymbolDetails> {
     pub timezone: String,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingRate {
+    pub symbol: String,
+    pub funding_rate: Decimal,
+    pub funding_time: Time,
+    // Empty for the oldest funding rates.
+    #[serde(
+        default,
+        deserialize_with = "crate::serde::deserialize_empty_string_as_none"
+    )]
+    pub mark_price: Option<Decimal>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingRateRequest<S>
+where
+    S: AsRef<str>,
+{
+    #[serde(serialize_with = "crate::serde::serialize_as_ref")]
+    pub symbol: S,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<Time>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<Time>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+impl<S> FundingRateRequest<S>
+where
+    S: AsRef<str>,
+{
+    pub fn new(symbol: S) -> Self {
+        Self {
+            symbol,
+            start_time: None,
+            end_time: None,
+            limit: None,
+        }
+    }
+
+    pub fn start_time<T>(mut self, start_time: T) -> Self
+    where
+        T: TryInto<Time>,
+    {
+        self.start_time = start_time.try_into().ok();
+        self
+    }
+
+    pub fn end_time<T>(mut self, end_time: T) -> Self
+    where
+        T: TryInto<Time>,
+    {
+        self.end_time = end_time.try_into().ok();
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+/// A request for the statistics of the `/futures/data` endpoints.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FuturesDataRequest<S>
+where
+    S: AsRef<str>,
+{
+    #[serde(serialize_with = "crate::serde::serialize_as_ref")]
+    pub symbol: S,
+    pub period: DataPeriod,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<Time>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<Time>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+impl<S> FuturesDataRequest<S>
+where
+    S: AsRef<str>,
+{
+    pub fn new(symbol: S, period: DataPeriod) -> Self {
+        Self {
+            symbol,
+            period,
+            start_time: None,
+            end_time: None,
+            limit: None,
+        }
+    }
+
+    pub fn start_time<T>(mut self, start_time: T) -> Self
+    where
+        T: TryInto<Time>,
+    {
+        self.start_time = start_time.try_into().ok();
+        self
+    }
+
+    pub fn end_time<T>(mut self, end_time: T) -> Self
+    where
+        T: TryInto<Time>,
+    {
+        self.end_time = end_time.try_into().ok();
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoricalTradesRequest<S>
+where
+    S: AsRef<str>,
+{
+    #[serde(serialize_with = "crate::serde::serialize_as_ref")]
+    pub symbol: S,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_id: Option<u64>,
+}
+
+impl<S> HistoricalTradesRequest<S>
+where
+    S: AsRef<str>,
+{
+    pub fn new(symbol: S) -> Self {
+        Self {
+            symbol,
+            limit: None,
+            from_id: None,
+        }
+    }
+
+    pub fn from_id(mut self, from_id: u64) -> Self {
+        self.from_id = Some(from_id);
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
 #[derive(Clone, Debug, Serialize)]
 pub struct KlinesRecord {
     #[serde(rename = "ot")]
@@ -401,7 +758,42 @@ impl<'de> Deserialize<'de> for KlinesRecord {
             }
         }
 
-        deserializer.deserialize_seq(KlinesVisitor)
+        deserializer.deserialize_seq(KlinesVisitor)
+    }
+}
+
+/// How [`Market::klines_range`](crate::market::Market::klines_range) fetches its pages.
+#[derive(Clone, Copy, Debug)]
+pub struct KlinesRangeOptions {
+    /// The number of pages fetched at once.
+    pub concurrency: usize,
+    /// End the stream with [`KlinesRangeError::Gap`](crate::error::KlinesRangeError::Gap) on missing klines, instead of logging a
+    /// warning.
+    pub fail_on_gaps: bool,
+}
+
+impl Default for KlinesRangeOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 1,
+            fail_on_gaps: false,
+        }
+    }
+}
+
+impl KlinesRangeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn fail_on_gaps(mut self, fail_on_gaps: bool) -> Self {
+        self.fail_on_gaps = fail_on_gaps;
+        self
     }
 }
 
@@ -462,7 +854,12 @@ where
 #[serde(rename_all = "camelCase")]
 pub struct Leverage {
     pub leverage: u8,
+    // Only present in USDⓈ-M Futures API.
+    #[serde(default)]
     pub max_notional_value: Decimal,
+    // Only present in COIN-M Futures API, in contracts.
+    #[serde(default)]
+    pub max_qty: Option<Decimal>,
     pub symbol: String,
 }
 
@@ -476,6 +873,17 @@ where
     pub leverage: u8,
 }
 
+/// The ratio of long to short accounts or positions.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LongShortRatio {
+    pub symbol: String,
+    pub long_short_ratio: Decimal,
+    pub long_account: Decimal,
+    pub short_account: Decimal,
+    pub timestamp: Time,
+}
+
 #[derive(Clone, Copy, Debug, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum MarginType {
@@ -652,6 +1060,40 @@ pub enum OCOOrderStatus {
     Reject,
 }
 
+// Ticker endpoints answer with an object when a single symbol is requested.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> From<OneOrMany<T>> for Vec<T> {
+    fn from(v: OneOrMany<T>) -> Self {
+        match v {
+            OneOrMany::One(t) => vec![t],
+            OneOrMany::Many(v) => v,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenInterest {
+    pub symbol: String,
+    pub open_interest: Decimal,
+    pub time: Time,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenInterestHist {
+    pub symbol: String,
+    pub sum_open_interest: Decimal,
+    pub sum_open_interest_value: Decimal,
+    pub timestamp: Time,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OrderBook {
@@ -716,6 +1158,28 @@ pub struct Order<Details, Type> {
     pub details: Details,
 }
 
+/// The details of a COIN-M Futures order, whose quantities are numbers of contracts.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DOrder {
+    pub avg_price: Decimal,
+    pub cum_qty: Decimal,
+    pub cum_base: Decimal,
+    pub orig_type: FOrderType,
+    pub pair: String,
+    pub reduce_only: bool,
+    pub position_side: PositionSide,
+    pub stop_price: Decimal,
+    pub close_position: bool,
+    #[serde(default)]
+    pub activate_price: Decimal,
+    #[serde(default)]
+    pub price_rate: Decimal,
+    pub working_type: WorkingType,
+    #[serde(default)]
+    pub price_protect: bool,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FOrder {
@@ -848,7 +1312,12 @@ pub struct Position {
     pub leverage: Decimal,
     pub liquidation_price: Decimal,
     pub mark_price: Decimal,
+    // Only present in USDⓈ-M Futures API.
+    #[serde(default)]
     pub max_notional_value: Decimal,
+    // Only present in COIN-M Futures API, in contracts.
+    #[serde(default)]
+    pub max_qty: Option<Decimal>,
     pub position_amt: Decimal,
     pub symbol: String,
     #[serde(rename = "unRealizedProfit")]
@@ -864,6 +1333,20 @@ pub enum PositionSide {
     Short,
 }
 
+/// The mark and index price of a symbol, and its predicted funding rate.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PremiumIndex {
+    pub symbol: String,
+    pub mark_price: Decimal,
+    pub index_price: Decimal,
+    pub estimated_settle_price: Decimal,
+    pub last_funding_rate: Decimal,
+    pub interest_rate: Decimal,
+    pub next_funding_time: Time,
+    pub time: Time,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct RateLimit {
     pub interval: RateLimitInterval,
@@ -882,7 +1365,7 @@ pub enum RateLimitInterval {
     Day,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum RateLimitType {
     RequestWeight,
@@ -901,12 +1384,21 @@ pub enum Status {
     Halt,
     AuctionMatch,
     Break,
+
+    // COIN-M Futures API contract statuses.
+    PreDelivering,
+    Delivering,
+    Delivered,
+    PreSettle,
+    Settling,
+    Close,
 }
 
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Symbol<OrderType, SymbolDetails> {
     pub symbol: String,
+    #[serde(alias = "contractStatus")]
     pub status: Status,
     pub base_asset: String,
     pub quote_asset: String,
@@ -925,6 +1417,24 @@ impl<O, S> AsRef<str> for Symbol<O, S> {
     }
 }
 
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DSymbol {
+    pub pair: String,
+    pub contract_type: ContractType,
+    /// The value of one contract, in units of the quote asset.
+    pub contract_size: Decimal,
+    pub delivery_date: Time,
+    pub onboard_date: Time,
+    pub margin_asset: String,
+    pub maint_margin_percent: Decimal,
+    pub required_margin_percent: Decimal,
+    pub price_precision: u8,
+    pub quantity_precision: u8,
+    pub equal_qty_precision: u8,
+    pub time_in_force: Vec<TimeInForce>,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FSymbol {
@@ -1016,6 +1526,17 @@ pub enum SymbolFilter {
     },
 }
 
+/// The parameters of a signed request, along with the `recvWindow` that it is valid for.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SignedRequest<T> {
+    #[serde(flatten)]
+    pub params: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recv_window: Option<u64>,
+}
+
+/// Selects one symbol, or all symbols.
 #[derive(Clone, Debug, Serialize)]
 pub struct SymbolRequest<S>
 where
@@ -1028,6 +1549,155 @@ where
     pub symbol: Option<S>,
 }
 
+impl<S> SymbolRequest<S>
+where
+    S: AsRef<str>,
+{
+    pub fn single(symbol: S) -> Self {
+        Self {
+            symbol: Some(symbol),
+        }
+    }
+}
+
+impl SymbolRequest<&'static str> {
+    pub fn all() -> Self {
+        Self { symbol: None }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TakerVolume {
+    pub buy_sell_ratio: Decimal,
+    #[serde(rename = "buyVol")]
+    pub buy_volume: Decimal,
+    #[serde(rename = "sellVol")]
+    pub sell_volume: Decimal,
+    pub timestamp: Time,
+}
+
+/// The 24 hour rolling window statistics of a symbol, with the fields of [`WSEventTicker`].
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ticker24hr {
+    pub symbol: String,
+    pub price_change: Decimal,
+    pub price_change_percent: Decimal,
+    pub weighted_avg_price: Decimal,
+    pub last_price: Decimal,
+    #[serde(rename = "lastQty")]
+    pub last_quantity: Decimal,
+    #[serde(rename = "openPrice")]
+    pub open: Decimal,
+    #[serde(rename = "highPrice")]
+    pub high: Decimal,
+    #[serde(rename = "lowPrice")]
+    pub low: Decimal,
+    #[serde(rename = "volume")]
+    pub base_asset_volume: Decimal,
+    // COIN-M Futures API reports the base asset volume, as its streams do.
+    #[serde(rename = "quoteVolume", alias = "baseVolume")]
+    pub quote_asset_volume: Decimal,
+    #[serde(rename = "openTime")]
+    pub stat_open_time: Time,
+    #[serde(rename = "closeTime")]
+    pub stat_close_time: Time,
+    // -1 when there were no trades in the window.
+    pub first_id: i64,
+    pub last_id: i64,
+    #[serde(rename = "count")]
+    pub num_trades: usize,
+}
+
+/// Converts a [`WSEventDetails::Ticker`] event, or returns any other event as is.
+impl<OrderType> TryFrom<WSEvent<OrderType>> for Ticker24hr {
+    type Error = WSEvent<OrderType>;
+
+    fn try_from(event: WSEvent<OrderType>) -> Result<Self, WSEvent<OrderType>> {
+        match event {
+            WSEvent {
+                symbol: Some(symbol),
+                details: WSEventDetails::Ticker(t),
+                ..
+            } => Ok(Self {
+                symbol,
+                price_change: t.price_change,
+                price_change_percent: t.price_change_percent,
+                weighted_avg_price: t.weighted_avg_price,
+                last_price: t.last_price,
+                last_quantity: t.last_quantity,
+                open: t.open,
+                high: t.high,
+                low: t.low,
+                base_asset_volume: t.base_asset_volume,
+                quote_asset_volume: t.quote_asset_volume,
+                stat_open_time: t.stat_open_time,
+                stat_close_time: t.stat_close_time,
+                first_id: t.first_id,
+                last_id: t.last_id,
+                num_trades: t.num_trades,
+            }),
+            event => Err(event),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TickerPrice {
+    pub symbol: String,
+    pub price: Decimal,
+    // Only present in Futures API.
+    #[serde(default)]
+    pub time: Option<Time>,
+}
+
+/// Selects the symbols of a ticker request. All symbols are requested by default.
+#[derive(Clone, Debug, Serialize)]
+pub struct TickerRequest<S>
+where
+    S: AsRef<str>,
+{
+    #[serde(
+        serialize_with = "crate::serde::serialize_optional_as_ref",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub symbol: Option<S>,
+    #[serde(
+        serialize_with = "crate::serde::serialize_optional_json_as_ref",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub symbols: Option<Vec<S>>,
+}
+
+impl<S> TickerRequest<S>
+where
+    S: AsRef<str>,
+{
+    pub fn single(symbol: S) -> Self {
+        Self {
+            symbol: Some(symbol),
+            symbols: None,
+        }
+    }
+
+    pub fn list(symbols: Vec<S>) -> Self {
+        Self {
+            symbol: None,
+            symbols: Some(symbols),
+        }
+    }
+}
+
+impl TickerRequest<&'static str> {
+    pub fn all() -> Self {
+        Self {
+            symbol: None,
+            symbols: None,
+        }
+    }
+}
+
 #[derive(
     Clone,
     Constructor,
@@ -1049,7 +1719,7 @@ pub struct Time(#[serde(with = "ts_milliseconds")] pub DateTime<Utc>);
 
 impl Default for Time {
     fn default() -> Self {
-        Time(Utc.timestamp(0, 0))
+        Time(Utc.timestamp_millis_opt(0).unwrap())
     }
 }
 
@@ -1100,6 +1770,75 @@ pub enum TimeInForce {
     GoodTilCrossing,
 }
 
+/// The trade at which a paginated trades stream stops, inclusively.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TradesBound {
+    Id(u64),
+    Time(Time),
+}
+
+impl TradesBound {
+    /// Whether `trade` lies beyond the bound when walking in `direction`.
+    pub fn is_passed_by(&self, trade: &TradesRecord, direction: Direction) -> bool {
+        self.is_passed_at(trade.id, trade.time, direction)
+    }
+
+    pub(crate) fn is_passed_at(&self, id: u64, time: Time, direction: Direction) -> bool {
+        match (self, direction) {
+            (TradesBound::Id(bound), Direction::Forward) => id > *bound,
+            (TradesBound::Id(bound), Direction::Backward) => id < *bound,
+            (TradesBound::Time(bound), Direction::Forward) => time > *bound,
+            (TradesBound::Time(bound), Direction::Backward) => time < *bound,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TradesRecord {
+    pub id: u64,
+    pub price: Decimal,
+    #[serde(rename = "qty")]
+    pub quantity: Decimal,
+    // COIN-M Futures API reports the base asset quantity instead.
+    #[serde(rename = "quoteQty", alias = "baseQty")]
+    pub quote_quantity: Decimal,
+    pub time: Time,
+    #[serde(rename = "isBuyerMaker")]
+    pub buyer_is_maker: bool,
+    // Only present in Spot API.
+    #[serde(default, rename = "isBestMatch")]
+    pub best_match: Option<bool>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TradesRequest<S>
+where
+    S: AsRef<str>,
+{
+    #[serde(serialize_with = "crate::serde::serialize_as_ref")]
+    pub symbol: S,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+impl<S> TradesRequest<S>
+where
+    S: AsRef<str>,
+{
+    pub fn new(symbol: S) -> Self {
+        Self {
+            symbol,
+            limit: None,
+        }
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum Type {
@@ -1109,6 +1848,98 @@ pub enum Type {
     Spot,
 }
 
+/// The window of a rolling window ticker: 1-59 minutes, 1-23 hours or 1-7 days. Converting a
+/// [`Duration`] uses the largest unit that it is a whole number of.
+#[derive(Clone, Copy, Debug, Display, Eq, PartialEq)]
+#[display(fmt = "{}{}", _0, _1)]
+pub struct WindowSize(u8, char);
+
+impl WindowSize {
+    pub fn minutes(minutes: u8) -> Option<Self> {
+        (1..=59).contains(&minutes).then_some(Self(minutes, 'm'))
+    }
+
+    pub fn hours(hours: u8) -> Option<Self> {
+        (1..=23).contains(&hours).then_some(Self(hours, 'h'))
+    }
+
+    pub fn days(days: u8) -> Option<Self> {
+        (1..=7).contains(&days).then_some(Self(days, 'd'))
+    }
+}
+
+impl Serialize for WindowSize {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl TryFrom<Duration> for WindowSize {
+    type Error = Duration;
+
+    fn try_from(d: Duration) -> Result<Self, Duration> {
+        let secs = d.as_secs();
+        if d.subsec_nanos() != 0 || !secs.is_multiple_of(60) {
+            return Err(d);
+        }
+
+        let size = match secs / 60 {
+            m if m.is_multiple_of(1440) => u8::try_from(m / 1440).ok().and_then(Self::days),
+            m if m.is_multiple_of(60) => u8::try_from(m / 60).ok().and_then(Self::hours),
+            m => u8::try_from(m).ok().and_then(Self::minutes),
+        };
+        size.ok_or(d)
+    }
+}
+
+/// The statistics of a symbol over a rolling window or a trading day. Mirrors [`Ticker24hr`],
+/// without the last quantity.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowTicker {
+    pub symbol: String,
+    pub price_change: Decimal,
+    pub price_change_percent: Decimal,
+    pub weighted_avg_price: Decimal,
+    pub last_price: Decimal,
+    #[serde(rename = "openPrice")]
+    pub open: Decimal,
+    #[serde(rename = "highPrice")]
+    pub high: Decimal,
+    #[serde(rename = "lowPrice")]
+    pub low: Decimal,
+    #[serde(rename = "volume")]
+    pub base_asset_volume: Decimal,
+    #[serde(rename = "quoteVolume")]
+    pub quote_asset_volume: Decimal,
+    #[serde(rename = "openTime")]
+    pub stat_open_time: Time,
+    #[serde(rename = "closeTime")]
+    pub stat_close_time: Time,
+    // -1 when there were no trades in the window.
+    pub first_id: i64,
+    pub last_id: i64,
+    #[serde(rename = "count")]
+    pub num_trades: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase", bound = "S: AsRef<str>")]
+pub(crate) struct WindowTickerRequest<'a, S>
+where
+    S: AsRef<str>,
+{
+    #[serde(flatten)]
+    pub tickers: TickerRequest<S>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_size: Option<WindowSize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<&'a str>,
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum WorkingType {
@@ -1128,7 +1959,8 @@ pub struct WSEvent<OrderType> {
 
 impl<OrderType> WSEvent<OrderType> {
     pub fn symbol(&self) -> Option<&str> {
-        self.symbol.as_deref().or_else(|| match &self.details {
+        self.symbol.as_deref().or(match &self.details {
+            WSEventDetails::ContinuousKline { pair, .. } => Some(pair.as_str()),
             WSEventDetails::ForceOrder {
                 details: WSEventForceOrder { symbol, .. },
             } => Some(symbol.as_str()),
@@ -1153,6 +1985,15 @@ pub enum WSEventDetails<OrderType> {
     },
     AggTrade(WSEventAggTrade),
     BookTicker(WSEventBookTicker),
+    #[serde(alias = "continuous_kline")]
+    ContinuousKline {
+        #[serde(alias = "ps")]
+        pair: String,
+        #[serde(alias = "ct")]
+        contract_type: ContractType,
+        #[serde(alias = "k")]
+        details: WSEventKline,
+    },
     ForceOrder {
         #[serde(alias = "o")]
         details: WSEventForceOrder<OrderType>,
@@ -1265,10 +2106,11 @@ pub struct WSEventAggTrade {
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct WSEventBookTicker {
-    #[serde(alias = "u")]
-    pub update_id: u64,
-    #[serde(alias = "T")]
-    pub transaction_time: Time,
+    #[serde(alias = "u", default)]
+    pub update_id: Option<u64>,
+    // Only present in Futures API.
+    #[serde(alias = "T", default)]
+    pub transaction_time: Option<Time>,
     #[serde(alias = "b")]
     pub best_bid_price: Decimal,
     #[serde(alias = "B")]
@@ -1509,14 +2351,16 @@ pub struct WSEventTicker {
     pub stat_open_time: Time,
     #[serde(alias = "C")]
     pub stat_close_time: Time,
+    // -1 when there were no trades in the window.
     #[serde(alias = "F")]
-    pub first_id: u64,
+    pub first_id: i64,
     #[serde(alias = "L")]
-    pub last_id: u64,
+    pub last_id: i64,
     #[serde(alias = "n")]
     pub num_trades: usize,
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug, Deserialize)]
 #[serde(untagged)]
 pub(crate) enum WSMessage<OrderType> {
@@ -1604,6 +2448,7 @@ pub struct WSResponse {
 }
 
 impl WSResponse {
+    #[allow(clippy::result_large_err)]
     pub fn result<T>(&self) -> Result<T, Error<WSApiCode>>
     where
         T: DeserializeOwned,
@@ -1641,6 +2486,13 @@ where
     BookDepthRealTime(S),
     #[display(fmt = "{}@bookTicker", "_0.as_ref().to_lowercase()")]
     BookTicker(S),
+    #[display(
+        fmt = "{}_{}@continuousKline_{}",
+        "_0.as_ref().to_lowercase()",
+        "_1.to_string().to_lowercase()",
+        "_2"
+    )]
+    ContinuousKline(S, ContractType, ChartInterval),
     #[display(fmt = "{}@forceOrder", "_0.as_ref().to_lowercase()")]
     ForceLiquidationOrder(S),
     #[display(fmt = "{}@kline_{}", "_0.as_ref().to_lowercase()", "_1")]
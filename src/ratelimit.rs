@@ -0,0 +1,352 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::{header::HeaderMap, Method};
+
+use crate::{
+    models::{RateLimit, RateLimitInterval, RateLimitType},
+    rt,
+};
+
+const USED_WEIGHT_HEADER: &str = "x-mbx-used-weight-";
+const ORDER_COUNT_HEADER: &str = "x-mbx-order-count-";
+
+/// The cost of a single request against each type of rate limit.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Weight {
+    pub weight: u32,
+    pub orders: u32,
+}
+
+impl Weight {
+    pub fn new(weight: u32) -> Self {
+        Self { weight, orders: 0 }
+    }
+
+    pub fn orders(mut self, orders: u32) -> Self {
+        self.orders = orders;
+        self
+    }
+
+    /// Looks up the weight of an endpoint, taking into account the parameters that the weight
+    /// depends on (e.g. `limit` or the absence of `symbol`). Unknown endpoints weigh 1.
+    pub fn of(method: &Method, path: &str, query: &str) -> Self {
+        let param = |name: &str| {
+            query.split('&').find_map(|pair| {
+                let mut kv = pair.splitn(2, '=');
+                match (kv.next(), kv.next()) {
+                    (Some(k), Some(v)) if k == name => Some(v.to_string()),
+                    _ => None,
+                }
+            })
+        };
+        let limit = param("limit").and_then(|l| l.parse::<u32>().ok());
+        let has_symbol = param("symbol").is_some();
+        // `symbols` is a URL encoded JSON array.
+        let symbols = param("symbols").map(|s| s.matches("%2C").count() + 1);
+
+        match (method, path) {
+            (_, "/api/v3/exchangeInfo") => Self::new(20),
+            (_, "/api/v3/aggTrades") => Self::new(2),
+            (_, "/fapi/v1/aggTrades") | (_, "/dapi/v1/aggTrades") => Self::new(20),
+            (_, "/api/v3/trades") | (_, "/api/v3/historicalTrades") => Self::new(25),
+            (_, "/fapi/v1/trades") | (_, "/dapi/v1/trades") => Self::new(5),
+            (_, "/fapi/v1/historicalTrades") | (_, "/dapi/v1/historicalTrades") => Self::new(20),
+            (_, "/api/v3/klines") => Self::new(2),
+            (_, "/fapi/v1/klines")
+            | (_, "/dapi/v1/klines")
+            | (_, "/fapi/v1/continuousKlines")
+            | (_, "/fapi/v1/indexPriceKlines")
+            | (_, "/fapi/v1/markPriceKlines")
+            | (_, "/fapi/v1/premiumIndexKlines") => Self::new(match limit.unwrap_or(500) {
+                0..=99 => 1,
+                100..=499 => 2,
+                500..=1000 => 5,
+                _ => 10,
+            }),
+            (_, "/api/v3/depth") => Self::new(match limit.unwrap_or(100) {
+                0..=100 => 5,
+                101..=500 => 25,
+                501..=1000 => 50,
+                _ => 250,
+            }),
+            (_, "/fapi/v1/depth") | (_, "/dapi/v1/depth") => {
+                Self::new(match limit.unwrap_or(500) {
+                    0..=50 => 2,
+                    51..=100 => 5,
+                    101..=500 => 10,
+                    _ => 20,
+                })
+            }
+            (_, "/api/v3/listenKey") => Self::new(2),
+            (_, "/api/v3/ticker/24hr") => Self::new(match (has_symbol, symbols) {
+                (true, _) => 2,
+                (_, Some(0..=20)) => 2,
+                (_, Some(21..=100)) => 40,
+                _ => 80,
+            }),
+            (_, "/api/v3/ticker") | (_, "/api/v3/ticker/tradingDay") => {
+                Self::new((symbols.unwrap_or(1) as u32 * 4).min(200))
+            }
+            (_, "/api/v3/avgPrice") => Self::new(2),
+            (_, "/fapi/v1/premiumIndex") => Self::new(if has_symbol { 1 } else { 10 }),
+            (_, "/fapi/v1/ticker/24hr") | (_, "/dapi/v1/ticker/24hr") => {
+                Self::new(if has_symbol { 1 } else { 40 })
+            }
+            (_, "/api/v3/ticker/price") | (_, "/api/v3/ticker/bookTicker") => {
+                Self::new(if has_symbol { 2 } else { 4 })
+            }
+            (_, "/fapi/v1/ticker/price") | (_, "/dapi/v1/ticker/price") => {
+                Self::new(if has_symbol { 1 } else { 2 })
+            }
+            (_, "/fapi/v1/ticker/bookTicker") | (_, "/dapi/v1/ticker/bookTicker") => {
+                Self::new(if has_symbol { 2 } else { 5 })
+            }
+
+            (&Method::POST, "/api/v3/order")
+            | (&Method::POST, "/fapi/v1/order")
+            | (&Method::POST, "/dapi/v1/order") => Self::new(1).orders(1),
+            (&Method::GET, "/api/v3/order") => Self::new(4),
+            (&Method::GET, "/api/v3/openOrders") => Self::new(if has_symbol { 6 } else { 80 }),
+            (&Method::GET, "/fapi/v1/openOrders") | (&Method::GET, "/dapi/v1/openOrders") => {
+                Self::new(if has_symbol { 1 } else { 40 })
+            }
+            (_, "/api/v3/allOrders") => Self::new(20),
+            (_, "/fapi/v1/allOrders") => Self::new(5),
+            (_, "/dapi/v1/allOrders") => Self::new(if has_symbol { 20 } else { 40 }),
+            (&Method::POST, "/fapi/v1/batchOrders") | (&Method::POST, "/dapi/v1/batchOrders") => {
+                Self::new(5).orders(5)
+            }
+            (_, "/fapi/v1/countdownCancelAll") | (_, "/dapi/v1/countdownCancelAll") => {
+                Self::new(10)
+            }
+            (&Method::GET, "/fapi/v1/positionSide/dual")
+            | (&Method::GET, "/dapi/v1/positionSide/dual") => Self::new(30),
+            (_, "/fapi/v2/balance") | (_, "/fapi/v2/positionRisk") => Self::new(5),
+
+            _ => Self::new(1),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    ty: RateLimitType,
+    interval: i64,
+    limit: u32,
+    used: u32,
+    window_start: i64,
+}
+
+impl Bucket {
+    fn roll(&mut self, now: i64) {
+        let window_start = now - now.rem_euclid(self.interval);
+        if window_start != self.window_start {
+            self.window_start = window_start;
+            self.used = 0;
+        }
+    }
+
+    fn cost(&self, weight: Weight) -> u32 {
+        match self.ty {
+            RateLimitType::RequestWeight => weight.weight,
+            RateLimitType::Orders => weight.orders,
+            RateLimitType::RawRequests => 1,
+        }
+    }
+
+    fn delay(&self, weight: Weight, now: i64) -> i64 {
+        let cost = self.cost(weight);
+
+        // A request that can never fit into a window is let through once the window is empty.
+        if cost == 0 || self.used + cost <= self.limit || self.used == 0 {
+            0
+        } else {
+            self.window_start + self.interval - now
+        }
+    }
+}
+
+/// A client-side rate limiter, shared by all clones of a [`Client`](crate::client::Client).
+///
+/// The limiter is seeded from the `rate_limits` of the exchange info, charges each request its
+/// endpoint [`Weight`] up front, delays requests that would exceed a limit until the limit's
+/// window resets, and resynchronizes with the usage reported by the `X-MBX-USED-WEIGHT-*` and
+/// `X-MBX-ORDER-COUNT-*` response headers.
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    buckets: Arc<Mutex<Vec<Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(rate_limits: &[RateLimit]) -> Self {
+        let buckets = rate_limits
+            .iter()
+            .map(|rl| Bucket {
+                ty: rl.ty,
+                interval: interval_millis(rl.interval, rl.num),
+                limit: rl.limit,
+                used: 0,
+                window_start: 0,
+            })
+            .collect();
+
+        Self {
+            buckets: Arc::new(Mutex::new(buckets)),
+        }
+    }
+
+    pub(crate) async fn acquire(&self, weight: Weight) {
+        loop {
+            let delay = {
+                let now = chrono::Utc::now().timestamp_millis();
+                let mut buckets = self.buckets.lock().unwrap();
+                let delay = buckets
+                    .iter_mut()
+                    .map(|b| {
+                        b.roll(now);
+                        b.delay(weight, now)
+                    })
+                    .max()
+                    .unwrap_or(0);
+
+                if delay <= 0 {
+                    for b in buckets.iter_mut() {
+                        b.used += b.cost(weight);
+                    }
+                    return;
+                }
+
+                delay
+            };
+
+            tracing::debug!(delay_ms = delay, "delaying request to respect rate limits");
+            rt::sleep(Duration::from_millis(delay as u64)).await;
+        }
+    }
+
+    pub(crate) fn update(&self, headers: &HeaderMap) {
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut buckets = self.buckets.lock().unwrap();
+
+        for (name, value) in headers {
+            let name = name.as_str();
+            let (ty, interval) = if let Some(interval) = name.strip_prefix(USED_WEIGHT_HEADER) {
+                (RateLimitType::RequestWeight, interval)
+            } else if let Some(interval) = name.strip_prefix(ORDER_COUNT_HEADER) {
+                (RateLimitType::Orders, interval)
+            } else {
+                continue;
+            };
+
+            let interval = match parse_interval(interval) {
+                Some(interval) => interval,
+                None => continue,
+            };
+            let used = match value.to_str().ok().and_then(|v| v.parse::<u32>().ok()) {
+                Some(used) => used,
+                None => continue,
+            };
+
+            for b in buckets
+                .iter_mut()
+                .filter(|b| b.ty == ty && b.interval == interval)
+            {
+                b.roll(now);
+                b.used = b.used.max(used);
+            }
+        }
+    }
+}
+
+fn interval_millis(interval: RateLimitInterval, num: u8) -> i64 {
+    let unit = match interval {
+        RateLimitInterval::Second => 1_000,
+        RateLimitInterval::Minute => 60_000,
+        RateLimitInterval::Day => 86_400_000,
+    };
+    unit * i64::from(num.max(1))
+}
+
+// Parses the interval suffix of a rate limit header, e.g. `1m` or `10s`.
+fn parse_interval(s: &str) -> Option<i64> {
+    let (num, unit) = s.split_at(s.len().checked_sub(1)?);
+    let num: i64 = num.parse().ok()?;
+    let unit = match unit {
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        "d" => 86_400_000,
+        _ => return None,
+    };
+    Some(num * unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate_limits() -> Vec<RateLimit> {
+        serde_json::from_str(
+            r#"[
+                {"rateLimitType": "REQUEST_WEIGHT", "interval": "MINUTE", "intervalNum": 1, "limit": 10},
+                {"rateLimitType": "ORDERS", "interval": "SECOND", "intervalNum": 10, "limit": 2}
+            ]"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn weight_of() {
+        assert_eq!(
+            Weight::of(&Method::GET, "/fapi/v1/depth", "symbol=BTCUSDT&limit=1000"),
+            Weight::new(20)
+        );
+        assert_eq!(
+            Weight::of(&Method::POST, "/api/v3/order", "symbol=BTCUSDT"),
+            Weight::new(1).orders(1)
+        );
+        assert_eq!(
+            Weight::of(&Method::GET, "/api/v3/openOrders", ""),
+            Weight::new(80)
+        );
+        assert_eq!(
+            Weight::of(
+                &Method::GET,
+                "/api/v3/ticker/24hr",
+                "symbols=%5B%22BTCUSDT%22%2C%22ETHUSDT%22%5D"
+            ),
+            Weight::new(2)
+        );
+        assert_eq!(
+            Weight::of(&Method::GET, "/api/v3/ticker/24hr", ""),
+            Weight::new(80)
+        );
+        assert_eq!(
+            Weight::of(
+                &Method::GET,
+                "/api/v3/ticker",
+                "symbols=%5B%22BTCUSDT%22%2C%22ETHUSDT%22%5D&windowSize=1h"
+            ),
+            Weight::new(8)
+        );
+        assert_eq!(Weight::of(&Method::GET, "/unknown", ""), Weight::new(1));
+    }
+
+    #[test]
+    fn update_from_headers() {
+        let limiter = RateLimiter::new(&rate_limits());
+        let mut headers = HeaderMap::new();
+        headers.insert("x-mbx-used-weight-1m", "7".parse().unwrap());
+        headers.insert("x-mbx-order-count-10s", "2".parse().unwrap());
+        limiter.update(&headers);
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets[0].used, 7);
+        assert_eq!(buckets[0].delay(Weight::new(3), now), 0);
+        assert!(buckets[0].delay(Weight::new(4), now) > 0);
+        assert!(buckets[1].delay(Weight::new(1).orders(1), now) > 0);
+        assert_eq!(buckets[1].delay(Weight::new(1), now), 0);
+    }
+}
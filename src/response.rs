@@ -0,0 +1,146 @@
+use std::result;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::{
+    header::{HeaderMap, DATE},
+    StatusCode,
+};
+
+use crate::error::{ApiCode, ContextError, Error};
+
+/// The result of a REST request, along with the metadata of the HTTP response.
+#[derive(Clone, Debug)]
+pub struct Response<T> {
+    pub data: T,
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// The round-trip time of the request, excluding any earlier attempts that were retried.
+    pub latency: Duration,
+}
+
+impl<T> Response<T> {
+    pub fn into_inner(self) -> T {
+        self.data
+    }
+
+    pub fn map<U, F>(self, f: F) -> Response<U>
+    where
+        F: FnOnce(T) -> U,
+    {
+        Response {
+            data: f(self.data),
+            status: self.status,
+            headers: self.headers,
+            latency: self.latency,
+        }
+    }
+
+    /// The request weight used by this IP in the current minute.
+    pub fn used_weight_1m(&self) -> Option<u32> {
+        self.header("x-mbx-used-weight-1m")
+    }
+
+    /// The number of orders placed by this account in the current 10 seconds.
+    pub fn order_count_10s(&self) -> Option<u32> {
+        self.header("x-mbx-order-count-10s")
+    }
+
+    /// The number of orders placed by this account in the current day.
+    pub fn order_count_1d(&self) -> Option<u32> {
+        self.header("x-mbx-order-count-1d")
+    }
+
+    /// The server time at which the response was sent, with a precision of one second.
+    pub fn date(&self) -> Option<DateTime<Utc>> {
+        let date = self.headers.get(DATE)?.to_str().ok()?;
+        DateTime::parse_from_rfc2822(date)
+            .ok()
+            .map(|d| d.with_timezone(&Utc))
+    }
+
+    fn header(&self, name: &str) -> Option<u32> {
+        self.headers.get(name)?.to_str().ok()?.parse().ok()
+    }
+}
+
+/// Determines what the `Exchange`, `Market`, `Account` and `Trade` providers return: either
+/// just the result of a request and an [`Error`] ([`Plain`]), or a [`Response`] with its
+/// metadata and a [`ContextError`] with the request that failed ([`WithMetadata`]).
+pub trait ResponseMode {
+    type Output<T>;
+    type Error<C: ApiCode>;
+
+    fn output<T>(resp: Response<T>) -> Self::Output<T>;
+
+    fn error<C: ApiCode>(err: ContextError<C>) -> Self::Error<C>;
+}
+
+/// The result of a provider method in the response mode `M`.
+pub type ModeResult<M, T, C> =
+    result::Result<<M as ResponseMode>::Output<T>, <M as ResponseMode>::Error<C>>;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Plain;
+
+impl ResponseMode for Plain {
+    type Output<T> = T;
+    type Error<C: ApiCode> = Error<C>;
+
+    fn output<T>(resp: Response<T>) -> T {
+        resp.data
+    }
+
+    fn error<C: ApiCode>(err: ContextError<C>) -> Error<C> {
+        err.error
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WithMetadata;
+
+impl ResponseMode for WithMetadata {
+    type Output<T> = Response<T>;
+    type Error<C: ApiCode> = ContextError<C>;
+
+    fn output<T>(resp: Response<T>) -> Response<T> {
+        resp
+    }
+
+    fn error<C: ApiCode>(err: ContextError<C>) -> ContextError<C> {
+        err
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn metadata() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-mbx-used-weight-1m", HeaderValue::from_static("42"));
+        headers.insert("x-mbx-order-count-10s", HeaderValue::from_static("3"));
+        headers.insert(
+            DATE,
+            HeaderValue::from_static("Wed, 01 Jun 2022 00:00:07 GMT"),
+        );
+        let resp = Response {
+            data: 1,
+            status: StatusCode::OK,
+            headers,
+            latency: Duration::from_millis(20),
+        };
+
+        assert_eq!(resp.used_weight_1m(), Some(42));
+        assert_eq!(resp.order_count_10s(), Some(3));
+        assert_eq!(resp.order_count_1d(), None);
+        assert_eq!(
+            resp.date().unwrap().to_rfc3339(),
+            "2022-06-01T00:00:07+00:00"
+        );
+        assert_eq!(resp.map(|d| d + 1).into_inner(), 2);
+    }
+}
@@ -0,0 +1,230 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::error::{ApiCode, Error};
+
+/// Decides whether, and after how long, a failed REST request is retried.
+///
+/// Only idempotent requests (`GET` and `PUT`) are retried unless
+/// [`RetryPolicy::retry_non_idempotent`] is enabled, so that e.g. a new order is never placed twice
+/// by accident. A `Retry-After` value sent by the server always takes precedence over the backoff.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: bool,
+    retry_on_ban: bool,
+    retry_on_network_error: bool,
+    retry_on_rate_limit: bool,
+    retry_on_server_error: bool,
+    retry_on_timeout: bool,
+    retry_non_idempotent: bool,
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: true,
+            retry_on_ban: false,
+            retry_on_network_error: true,
+            retry_on_rate_limit: true,
+            retry_on_server_error: true,
+            retry_on_timeout: true,
+            retry_non_idempotent: false,
+        }
+    }
+
+    /// A policy that never retries.
+    pub fn never() -> Self {
+        Self::new().max_attempts(1)
+    }
+
+    /// The total number of attempts, including the first one.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Retries after an IP ban (HTTP 418) once its `Retry-After` has elapsed.
+    pub fn retry_on_ban(mut self, retry_on_ban: bool) -> Self {
+        self.retry_on_ban = retry_on_ban;
+        self
+    }
+
+    /// Retries after a connection error or a request timeout of the HTTP client, or after any
+    /// error of a custom [`Transport`](crate::transport::Transport).
+    pub fn retry_on_network_error(mut self, retry_on_network_error: bool) -> Self {
+        self.retry_on_network_error = retry_on_network_error;
+        self
+    }
+
+    /// Retries after the request rate limit (HTTP 429) or a firewall limit (HTTP 403) has been
+    /// reached.
+    pub fn retry_on_rate_limit(mut self, retry_on_rate_limit: bool) -> Self {
+        self.retry_on_rate_limit = retry_on_rate_limit;
+        self
+    }
+
+    /// Retries after an internal server error (HTTP 5xx).
+    pub fn retry_on_server_error(mut self, retry_on_server_error: bool) -> Self {
+        self.retry_on_server_error = retry_on_server_error;
+        self
+    }
+
+    /// Retries after an API timeout (HTTP 503).
+    pub fn retry_on_timeout(mut self, retry_on_timeout: bool) -> Self {
+        self.retry_on_timeout = retry_on_timeout;
+        self
+    }
+
+    /// Also retries non-idempotent requests, such as placing an order. Note that a request that
+    /// failed with a server error or timeout may still have been executed by the exchange.
+    pub fn retry_non_idempotent(mut self, retry_non_idempotent: bool) -> Self {
+        self.retry_non_idempotent = retry_non_idempotent;
+        self
+    }
+
+    /// Returns the delay before the next attempt, or `None` if `err` should be returned as is.
+    /// `attempt` is the number of attempts made so far.
+    pub(crate) fn backoff<C>(
+        &self,
+        attempt: u32,
+        idempotent: bool,
+        err: &Error<C>,
+    ) -> Option<Duration>
+    where
+        C: ApiCode,
+    {
+        if attempt >= self.max_attempts || !(idempotent || self.retry_non_idempotent) {
+            return None;
+        }
+
+        let retry_after = match err {
+            Error::ApiTimeout if self.retry_on_timeout => None,
+            Error::HttpRequest(e)
+                if self.retry_on_network_error && (e.is_connect() || e.is_timeout()) =>
+            {
+                None
+            }
+            Error::Transport(_) if self.retry_on_network_error => None,
+            Error::IPAddressBanned { retry_after } if self.retry_on_ban => *retry_after,
+            Error::FirewallLimitReached { retry_after }
+            | Error::RequestRateLimitReached { retry_after }
+                if self.retry_on_rate_limit =>
+            {
+                *retry_after
+            }
+            Error::Server(_) if self.retry_on_server_error => None,
+            Error::UnexpectedResponse { status, .. }
+                if self.retry_on_server_error && status.is_server_error() =>
+            {
+                None
+            }
+            _ => return None,
+        };
+
+        Some(retry_after.unwrap_or_else(|| self.exponential_backoff(attempt)))
+    }
+
+    fn exponential_backoff(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .mul_f64(self.multiplier.powi(attempt.saturating_sub(1) as i32))
+            .min(self.max_backoff);
+
+        if self.jitter {
+            // Equal jitter: keep half of the backoff and randomize the other half.
+            let half = backoff / 2;
+            half + half.mul_f64(rand::thread_rng().gen::<f64>())
+        } else {
+            backoff
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::FApiCode;
+
+    #[test]
+    fn backoff() {
+        let policy = RetryPolicy::new()
+            .initial_backoff(Duration::from_secs(1))
+            .max_backoff(Duration::from_secs(3))
+            .jitter(false);
+        let err = Error::<FApiCode>::ApiTimeout;
+
+        assert_eq!(policy.backoff(1, true, &err), Some(Duration::from_secs(1)));
+        assert_eq!(policy.backoff(2, true, &err), Some(Duration::from_secs(2)));
+        assert_eq!(policy.backoff(3, true, &err), None);
+        assert_eq!(policy.backoff(1, false, &err), None);
+        assert_eq!(
+            policy.clone().max_attempts(5).backoff(4, true, &err),
+            Some(Duration::from_secs(3))
+        );
+    }
+
+    #[test]
+    fn retry_after() {
+        let policy = RetryPolicy::new();
+        let rate_limited = Error::<FApiCode>::RequestRateLimitReached {
+            retry_after: Some(Duration::from_secs(30)),
+        };
+        let firewall = Error::<FApiCode>::FirewallLimitReached { retry_after: None };
+        let banned = Error::<FApiCode>::IPAddressBanned {
+            retry_after: Some(Duration::from_secs(120)),
+        };
+
+        assert_eq!(
+            policy.backoff(1, true, &rate_limited),
+            Some(Duration::from_secs(30))
+        );
+        assert!(policy.backoff(1, true, &firewall).is_some());
+        assert_eq!(
+            policy
+                .clone()
+                .retry_on_rate_limit(false)
+                .backoff(1, true, &firewall),
+            None
+        );
+        assert_eq!(policy.backoff(1, true, &banned), None);
+        assert_eq!(
+            policy.retry_on_ban(true).backoff(1, true, &banned),
+            Some(Duration::from_secs(120))
+        );
+    }
+}
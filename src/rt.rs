@@ -0,0 +1,133 @@
+//! The async runtime that websocket dispatchers, timers and connections run on, selected with the
+//! `runtime-tokio` (default) or `runtime-async-std` feature.
+
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use futures::future::{self, Either};
+
+#[cfg(not(any(feature = "runtime-tokio", feature = "runtime-async-std")))]
+compile_error!("either the `runtime-tokio` or the `runtime-async-std` feature must be enabled");
+
+#[cfg(feature = "runtime-tokio")]
+pub(crate) use self::tokio_rt::*;
+
+#[cfg(all(feature = "runtime-async-std", not(feature = "runtime-tokio")))]
+pub(crate) use self::async_std_rt::*;
+
+/// Resolves to `None` if `f` does not complete within `duration`.
+pub(crate) async fn timeout<F>(duration: Duration, f: F) -> Option<F::Output>
+where
+    F: Future,
+{
+    futures::pin_mut!(f);
+    let sleep = sleep(duration);
+    futures::pin_mut!(sleep);
+
+    match future::select(f, sleep).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(_) => None,
+    }
+}
+
+#[cfg(feature = "runtime-tokio")]
+mod tokio_rt {
+    use super::*;
+
+    pub(crate) use async_tungstenite::tokio::{
+        client_async_tls_with_connector_and_config, ClientStream,
+    };
+    pub(crate) use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+        time::sleep,
+    };
+    pub(crate) use tokio_native_tls::TlsConnector;
+
+    /// A socket that SOCKS5 handshakes can be made on.
+    pub(crate) type SocksSocket = TcpStream;
+
+    pub(crate) fn tls_connector(
+        builder: native_tls::TlsConnectorBuilder,
+    ) -> native_tls::Result<TlsConnector> {
+        builder.build().map(TlsConnector::from)
+    }
+
+    pub(crate) fn spawn<F>(f: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        tokio::spawn(f);
+    }
+
+    pub(crate) async fn lookup_host(host: &str, port: u16) -> io::Result<Option<SocketAddr>> {
+        Ok(tokio::net::lookup_host((host, port)).await?.next())
+    }
+
+    pub(crate) fn socks_socket(socket: TcpStream) -> SocksSocket {
+        socket
+    }
+
+    pub(crate) fn from_socks_socket(socket: SocksSocket) -> TcpStream {
+        socket
+    }
+}
+
+#[cfg(all(feature = "runtime-async-std", not(feature = "runtime-tokio")))]
+mod async_std_rt {
+    use async_std::net::ToSocketAddrs;
+    use tokio_socks::io::Compat;
+
+    use super::*;
+
+    pub(crate) use async_native_tls::TlsConnector;
+    pub(crate) use async_std::{net::TcpStream, task::sleep};
+    pub(crate) use async_tungstenite::async_std::{
+        client_async_tls_with_connector_and_config, ClientStream,
+    };
+    pub(crate) use futures::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// A socket that SOCKS5 handshakes can be made on.
+    pub(crate) type SocksSocket = Compat<TcpStream>;
+
+    pub(crate) fn tls_connector(
+        builder: native_tls::TlsConnectorBuilder,
+    ) -> native_tls::Result<TlsConnector> {
+        Ok(TlsConnector::from(builder))
+    }
+
+    pub(crate) fn spawn<F>(f: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        async_std::task::spawn(f);
+    }
+
+    pub(crate) async fn lookup_host(host: &str, port: u16) -> io::Result<Option<SocketAddr>> {
+        Ok((host, port).to_socket_addrs().await?.next())
+    }
+
+    pub(crate) fn socks_socket(socket: TcpStream) -> SocksSocket {
+        Compat::new(socket)
+    }
+
+    pub(crate) fn from_socks_socket(socket: SocksSocket) -> TcpStream {
+        socket.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn timeout_elapses() {
+        assert_eq!(timeout(Duration::from_secs(1), async { 1 }).await, Some(1));
+        assert_eq!(
+            timeout(Duration::from_millis(10), future::pending::<()>()).await,
+            None
+        );
+    }
+}
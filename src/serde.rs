@@ -1,4 +1,5 @@
 use std::fmt;
+use std::str::FromStr;
 
 use serde::{
     de,
@@ -38,6 +39,20 @@ where
     serializer.serialize_str(&json)
 }
 
+pub(crate) fn serialize_optional_json_as_ref<S, T>(
+    value: &Option<Vec<T>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: ser::Serializer,
+    T: AsRef<str>,
+{
+    match value {
+        Some(v) => serialize_json(&v.iter().map(T::as_ref).collect::<Vec<_>>(), serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
 pub(crate) fn deserialize_string_as_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
 where
     D: de::Deserializer<'de>,
@@ -55,12 +70,26 @@ where
         where
             E: de::Error,
         {
-            Ok(match v.to_lowercase().chars().nth(0) {
-                Some('t') | Some('1') => true,
-                _ => false,
-            })
+            Ok(matches!(
+                v.to_lowercase().chars().next(),
+                Some('t') | Some('1')
+            ))
         }
     }
 
     deserializer.deserialize_str(StringVisitor)
 }
+
+pub(crate) fn deserialize_empty_string_as_none<'de, D, T>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error>
+where
+    D: de::Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    match <Option<String> as de::Deserialize>::deserialize(deserializer)? {
+        Some(s) if !s.is_empty() => s.parse().map(Some).map_err(de::Error::custom),
+        _ => Ok(None),
+    }
+}
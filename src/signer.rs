@@ -0,0 +1,42 @@
+use std::fmt;
+use std::sync::Arc;
+
+use futures::future::{self, BoxFuture, FutureExt};
+
+use crate::{credentials::Credentials, transport::BoxError};
+
+/// Signs the requests of a [`Client`](crate::client::Client).
+///
+/// [`Credentials`] sign in-process. Implement this trait to delegate signing to a service that
+/// holds the private key, such as a signing daemon or an HSM. Errors returned by a signer surface
+/// as [`Error::Signing`](crate::error::Error::Signing).
+pub trait Signer: fmt::Debug + Send + Sync + 'static {
+    /// The API key sent in the `X-MBX-APIKEY` header of every request.
+    fn api_key(&self) -> &str;
+
+    /// Signs the query string of a request. The signature is URL encoded by the client.
+    fn sign(&self, payload: String) -> BoxFuture<'_, Result<String, BoxError>>;
+}
+
+impl<T> Signer for Arc<T>
+where
+    T: Signer + ?Sized,
+{
+    fn api_key(&self) -> &str {
+        (**self).api_key()
+    }
+
+    fn sign(&self, payload: String) -> BoxFuture<'_, Result<String, BoxError>> {
+        (**self).sign(payload)
+    }
+}
+
+impl Signer for Credentials {
+    fn api_key(&self) -> &str {
+        Credentials::api_key(self)
+    }
+
+    fn sign(&self, payload: String) -> BoxFuture<'_, Result<String, BoxError>> {
+        future::ready(Ok(Credentials::sign(self, payload))).boxed()
+    }
+}
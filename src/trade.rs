@@ -1,153 +1,258 @@
-use derive_more::Constructor;
+use std::marker::PhantomData;
+use std::time::Duration;
+
 use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{
-    client::{Api, Client, FApi, SApi},
-    error::Result,
+    client::{Api, Client, DApi, FApi, SApi},
     models::*,
+    response::{ModeResult, Plain, ResponseMode, WithMetadata},
 };
 
+pub type DTrade = Trade<DApi>;
 pub type FTrade = Trade<FApi>;
 pub type STrade = Trade<SApi>;
 
-#[derive(Clone, Constructor, Debug)]
-pub struct Trade<A: Api + TradeApi> {
+#[derive(Clone, Debug)]
+pub struct Trade<A: Api + TradeApi, M = Plain> {
     client: Client<A>,
+    recv_window: Option<Duration>,
+    _mode: PhantomData<M>,
 }
 
 impl<A> Trade<A>
 where
     A: Api + TradeApi,
+{
+    pub fn new(client: Client<A>) -> Self {
+        Self {
+            client,
+            recv_window: None,
+            _mode: PhantomData,
+        }
+    }
+
+    /// Makes every call return a [`Response`](crate::response::Response) carrying the metadata
+    /// of the HTTP response, such as the used request weight, or a
+    /// [`ContextError`](crate::error::ContextError) carrying the request that failed.
+    pub fn with_metadata(self) -> Trade<A, WithMetadata> {
+        Trade {
+            client: self.client,
+            recv_window: self.recv_window,
+            _mode: PhantomData,
+        }
+    }
+}
+
+impl<A, M> Trade<A, M>
+where
+    A: Api + TradeApi,
+{
+    /// Sets the `recvWindow` of every signed request, instead of the default of the client.
+    pub fn recv_window(mut self, recv_window: Duration) -> Self {
+        self.recv_window = Some(recv_window);
+        self
+    }
+
+    fn signed<T>(&self, params: T) -> SignedRequest<T> {
+        SignedRequest {
+            params,
+            recv_window: self.recv_window.map(|w| w.as_millis() as u64),
+        }
+    }
+}
+
+impl<A, M> Trade<A, M>
+where
+    A: Api + TradeApi,
+    M: ResponseMode,
 {
     pub async fn all_orders<S>(
         &self,
         req: AllOrdersRequest<S>,
-    ) -> Result<Order<A::OrderDetails, A::OrderType>, A::ErrorCode>
+    ) -> ModeResult<M, Order<A::OrderDetails, A::OrderType>, A::ErrorCode>
     where
         S: AsRef<str>,
     {
-        self.client.get(A::all_orders(), req).await
+        self.client
+            .get(A::all_orders(), self.signed(req))
+            .await
+            .map_err(M::error)
+            .map(M::output)
     }
 
-    pub async fn auto_cancel_all<S>(
-        &self,
-        symbol: S,
-        countdown_time: u64,
-    ) -> Result<(), A::ErrorCode>
+    pub async fn cancel_all_open_orders<S>(&self, symbol: S) -> ModeResult<M, (), A::ErrorCode>
     where
         S: AsRef<str>,
     {
-        let _: serde_json::Value = self
-            .client
-            .post(
-                A::auto_cancel_all(),
-                AutoCancelAllRequest {
-                    symbol,
-                    countdown_time,
-                },
+        let symbol = Some(symbol);
+        self.client
+            .delete::<_, serde_json::Value>(
+                A::all_open_orders(),
+                self.signed(SymbolRequest { symbol }),
             )
-            .await?;
-        Ok(())
+            .await
+            .map_err(M::error)
+            .map(|r| M::output(r.map(|_| ())))
     }
 
-    pub async fn cancel_all_open_orders<S>(&self, symbol: S) -> Result<(), A::ErrorCode>
+    pub async fn cancel_order<S>(
+        &self,
+        symbol: S,
+        order_id: u64,
+    ) -> ModeResult<M, Order<A::OrderDetails, A::OrderType>, A::ErrorCode>
     where
         S: AsRef<str>,
     {
-        let symbol = Some(symbol);
-        let _: serde_json::Value = self
-            .client
-            .delete(A::all_open_orders(), SymbolRequest { symbol })
-            .await?;
-        Ok(())
+        self.client
+            .delete(
+                A::order(),
+                self.signed(OrderRequest::single(symbol, order_id)),
+            )
+            .await
+            .map_err(M::error)
+            .map(M::output)
     }
 
-    pub async fn cancel_batch_orders<S>(
+    pub async fn new_order<S>(
         &self,
-        symbol: S,
-        order_ids: Vec<u64>,
-    ) -> Result<Vec<Order<A::OrderDetails, A::OrderType>>, A::ErrorCode>
+        req: NewOrderRequest<A::OrderRequestDetails, A::OrderType, S>,
+    ) -> ModeResult<M, Order<A::OrderDetails, A::OrderType>, A::ErrorCode>
     where
         S: AsRef<str>,
     {
         self.client
-            .delete(A::batch_orders(), OrderRequest::list(symbol, order_ids))
+            .post(A::order(), self.signed(req))
             .await
+            .map_err(M::error)
+            .map(M::output)
     }
 
-    pub async fn cancel_order<S>(
+    pub async fn open_orders<S>(
         &self,
-        symbol: S,
-        order_id: u64,
-    ) -> Result<Order<A::OrderDetails, A::OrderType>, A::ErrorCode>
+        symbol: Option<S>,
+    ) -> ModeResult<M, Vec<Order<A::OrderDetails, A::OrderType>>, A::ErrorCode>
     where
         S: AsRef<str>,
     {
         self.client
-            .delete(A::order(), OrderRequest::single(symbol, order_id))
+            .get(A::open_orders(), self.signed(SymbolRequest { symbol }))
             .await
+            .map_err(M::error)
+            .map(M::output)
     }
 
-    pub async fn leverage<S>(&self, symbol: S, leverage: u8) -> Result<Leverage, A::ErrorCode>
+    pub async fn query_order<S>(
+        &self,
+        symbol: S,
+        order_id: u64,
+    ) -> ModeResult<M, Order<A::OrderDetails, A::OrderType>, A::ErrorCode>
     where
         S: AsRef<str>,
     {
         self.client
-            .post(A::leverage(), LeverageRequest { symbol, leverage })
+            .get(
+                A::order(),
+                self.signed(OrderRequest::single(symbol, order_id)),
+            )
             .await
+            .map_err(M::error)
+            .map(M::output)
     }
+}
 
-    pub async fn new_batch_orders<S>(
+impl<A, M> Trade<A, M>
+where
+    A: Api + AutoCancelApi,
+    M: ResponseMode,
+{
+    pub async fn auto_cancel_all<S>(
         &self,
-        batch_orders: Vec<NewOrderRequest<A::OrderRequestDetails, A::OrderType, S>>,
-    ) -> Result<Vec<BatchOrder<A::OrderDetails, A::OrderType, A::ErrorCode>>, A::ErrorCode>
+        symbol: S,
+        countdown_time: u64,
+    ) -> ModeResult<M, (), A::ErrorCode>
     where
         S: AsRef<str>,
     {
-        #[derive(Serialize)]
-        #[serde(rename_all = "camelCase")]
-        struct BatchOrders<O: Serialize> {
-            batch_orders: Vec<O>,
-        }
-
         self.client
-            .post(A::batch_orders(), BatchOrders { batch_orders })
+            .post::<_, serde_json::Value>(
+                A::auto_cancel_all(),
+                self.signed(AutoCancelAllRequest {
+                    symbol,
+                    countdown_time,
+                }),
+            )
             .await
+            .map_err(M::error)
+            .map(|r| M::output(r.map(|_| ())))
     }
+}
 
-    pub async fn new_order<S>(
+impl<A, M> Trade<A, M>
+where
+    A: Api + BatchOrderApi,
+    M: ResponseMode,
+{
+    pub async fn cancel_batch_orders<S>(
         &self,
-        req: NewOrderRequest<A::OrderRequestDetails, A::OrderType, S>,
-    ) -> Result<Order<A::OrderDetails, A::OrderType>, A::ErrorCode>
+        symbol: S,
+        order_ids: Vec<u64>,
+    ) -> ModeResult<M, Vec<Order<A::OrderDetails, A::OrderType>>, A::ErrorCode>
     where
         S: AsRef<str>,
     {
-        self.client.post(A::order(), req).await
+        self.client
+            .delete(
+                A::batch_orders(),
+                self.signed(OrderRequest::list(symbol, order_ids)),
+            )
+            .await
+            .map_err(M::error)
+            .map(M::output)
     }
 
-    pub async fn open_orders<S>(
+    pub async fn new_batch_orders<S>(
         &self,
-        symbol: Option<S>,
-    ) -> Result<Vec<Order<A::OrderDetails, A::OrderType>>, A::ErrorCode>
+        batch_orders: Vec<NewOrderRequest<A::OrderRequestDetails, A::OrderType, S>>,
+    ) -> ModeResult<M, Vec<BatchOrder<A::OrderDetails, A::OrderType, A::ErrorCode>>, A::ErrorCode>
     where
         S: AsRef<str>,
     {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct BatchOrders<O: Serialize> {
+            batch_orders: Vec<O>,
+        }
+
         self.client
-            .get(A::open_orders(), SymbolRequest { symbol })
+            .post(A::batch_orders(), self.signed(BatchOrders { batch_orders }))
             .await
+            .map_err(M::error)
+            .map(M::output)
     }
+}
 
-    pub async fn query_order<S>(
+impl<A, M> Trade<A, M>
+where
+    A: Api + LeverageApi,
+    M: ResponseMode,
+{
+    pub async fn leverage<S>(
         &self,
         symbol: S,
-        order_id: u64,
-    ) -> Result<Order<A::OrderDetails, A::OrderType>, A::ErrorCode>
+        leverage: u8,
+    ) -> ModeResult<M, Leverage, A::ErrorCode>
     where
         S: AsRef<str>,
     {
         self.client
-            .get(A::order(), OrderRequest::single(symbol, order_id))
+            .post(
+                A::leverage(),
+                self.signed(LeverageRequest { symbol, leverage }),
+            )
             .await
+            .map_err(M::error)
+            .map(M::output)
     }
 }
 
@@ -158,36 +263,101 @@ pub trait TradeApi {
 
     fn all_orders() -> &'static str;
     fn all_open_orders() -> &'static str;
+    fn open_orders() -> &'static str;
+    fn order() -> &'static str;
+}
+
+/// Implemented by the APIs that can cancel all open orders after a countdown, i.e. not the Spot
+/// API.
+///
+/// ```compile_fail
+/// use binance_api::{client::SClient, trade::Trade};
+///
+/// # async fn auto_cancel_all() {
+/// let _ = Trade::new(SClient::new()).auto_cancel_all("BTCUSDT", 10000).await;
+/// # }
+/// ```
+pub trait AutoCancelApi: TradeApi {
     fn auto_cancel_all() -> &'static str;
+}
+
+/// Implemented by the APIs that can place and cancel orders in batches, i.e. not the Spot API.
+///
+/// ```compile_fail
+/// use binance_api::{client::SClient, trade::Trade};
+///
+/// # async fn cancel_batch_orders() {
+/// let _ = Trade::new(SClient::new()).cancel_batch_orders("BTCUSDT", vec![1]).await;
+/// # }
+/// ```
+pub trait BatchOrderApi: TradeApi {
     fn batch_orders() -> &'static str;
+}
+
+/// Implemented by the APIs that trade on leverage, i.e. not the Spot API.
+///
+/// ```compile_fail
+/// use binance_api::{client::SClient, trade::Trade};
+///
+/// # async fn leverage() {
+/// let _ = Trade::new(SClient::new()).leverage("BTCUSDT", 10).await;
+/// # }
+/// ```
+pub trait LeverageApi: TradeApi {
     fn leverage() -> &'static str;
-    fn open_orders() -> &'static str;
-    fn order() -> &'static str;
 }
 
-impl TradeApi for FApi {
+impl TradeApi for DApi {
     type OrderRequestDetails = FNewOrderRequest;
-    type OrderDetails = FOrder;
+    type OrderDetails = DOrder;
     type OrderType = FOrderType;
 
     fn all_orders() -> &'static str {
-        "/fapi/v1/allOrders"
+        "/dapi/v1/allOrders"
     }
 
     fn all_open_orders() -> &'static str {
-        "/fapi/v1/allOpenOrders"
+        "/dapi/v1/allOpenOrders"
+    }
+
+    fn open_orders() -> &'static str {
+        "/dapi/v1/openOrders"
+    }
+
+    fn order() -> &'static str {
+        "/dapi/v1/order"
     }
+}
 
+impl AutoCancelApi for DApi {
     fn auto_cancel_all() -> &'static str {
-        "/fapi/v1/countdownCancelAll"
+        "/dapi/v1/countdownCancelAll"
     }
+}
 
+impl BatchOrderApi for DApi {
     fn batch_orders() -> &'static str {
-        "/fapi/v1/batchOrders"
+        "/dapi/v1/batchOrders"
     }
+}
 
+impl LeverageApi for DApi {
     fn leverage() -> &'static str {
-        "/fapi/v1/leverage"
+        "/dapi/v1/leverage"
+    }
+}
+
+impl TradeApi for FApi {
+    type OrderRequestDetails = FNewOrderRequest;
+    type OrderDetails = FOrder;
+    type OrderType = FOrderType;
+
+    fn all_orders() -> &'static str {
+        "/fapi/v1/allOrders"
+    }
+
+    fn all_open_orders() -> &'static str {
+        "/fapi/v1/allOpenOrders"
     }
 
     fn open_orders() -> &'static str {
@@ -199,6 +369,24 @@ impl TradeApi for FApi {
     }
 }
 
+impl AutoCancelApi for FApi {
+    fn auto_cancel_all() -> &'static str {
+        "/fapi/v1/countdownCancelAll"
+    }
+}
+
+impl BatchOrderApi for FApi {
+    fn batch_orders() -> &'static str {
+        "/fapi/v1/batchOrders"
+    }
+}
+
+impl LeverageApi for FApi {
+    fn leverage() -> &'static str {
+        "/fapi/v1/leverage"
+    }
+}
+
 impl TradeApi for SApi {
     type OrderRequestDetails = SNewOrderRequest;
     type OrderDetails = SOrder;
@@ -212,18 +400,6 @@ impl TradeApi for SApi {
         "/api/v3/openOrders"
     }
 
-    fn auto_cancel_all() -> &'static str {
-        unimplemented!("Spot API does not support an auto cancel API.")
-    }
-
-    fn batch_orders() -> &'static str {
-        unimplemented!("Spot API does not support submitting batch orders.");
-    }
-
-    fn leverage() -> &'static str {
-        unimplemented!("Spot API does not support leverage trading.");
-    }
-
     fn open_orders() -> &'static str {
         "/api/v3/openOrders"
     }
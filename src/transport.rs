@@ -0,0 +1,76 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::Arc;
+
+use futures::future::{BoxFuture, FutureExt};
+use reqwest::{header::HeaderMap, Method, StatusCode};
+
+pub type BoxError = Box<dyn StdError + Send + Sync>;
+
+#[derive(Clone, Debug)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: HeaderMap,
+}
+
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+/// Sends the HTTP requests of a [`Client`](crate::client::Client).
+///
+/// Errors returned by a transport surface as [`Error::Transport`](crate::error::Error::Transport),
+/// except for `reqwest::Error`s which surface as
+/// [`Error::HttpRequest`](crate::error::Error::HttpRequest).
+pub trait Transport: fmt::Debug + Send + Sync + 'static {
+    fn send(&self, req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BoxError>>;
+}
+
+impl<T> Transport for Arc<T>
+where
+    T: Transport + ?Sized,
+{
+    fn send(&self, req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BoxError>> {
+        (**self).send(req)
+    }
+}
+
+/// The default transport, backed by a `reqwest::Client`.
+#[derive(Clone, Debug, Default)]
+pub struct ReqwestTransport {
+    http: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(http: reqwest::Client) -> Self {
+        Self { http }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BoxError>> {
+        let send = self
+            .http
+            .request(req.method, &req.url)
+            .headers(req.headers)
+            .send();
+
+        async move {
+            let resp = send.await?;
+            let status = resp.status();
+            let headers = resp.headers().clone();
+            let body = resp.bytes().await?.to_vec();
+
+            Ok(HttpResponse {
+                status,
+                headers,
+                body,
+            })
+        }
+        .boxed()
+    }
+}
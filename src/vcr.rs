@@ -0,0 +1,435 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use async_tungstenite::tungstenite::{Error as WsError, Message};
+use futures::{
+    channel::mpsc,
+    future::{self, BoxFuture, FutureExt},
+    sink::SinkExt,
+    stream::{self, StreamExt},
+};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+use crate::{
+    client::{Api, Client},
+    ws::{WSApi, WSClient, WSClientStream},
+};
+use crate::{
+    transport::{BoxError, HttpRequest, HttpResponse, ReqwestTransport, Transport},
+    ws::{WSSink, WSSource},
+};
+
+// Query parameters that change on every request or leak secrets, and are not recorded.
+const SCRUBBED_PARAMS: &[&str] = &["recvWindow", "signature", "timestamp"];
+
+/// A recording of REST and websocket traffic, for deterministic replay in tests.
+///
+/// In record mode the REST interactions of a [`Client`](crate::client::Client) using
+/// [`Cassette::transport`], and the frames of a [`WSClient`](crate::ws::WSClient) built with
+/// [`WSClientBuilder::cassette`](crate::ws::WSClientBuilder::cassette), are captured and written to
+/// the cassette file when the last handle to the cassette is dropped (or on [`Cassette::save`]).
+/// Request signatures, timestamps and API keys are never recorded.
+///
+/// In replay mode no connection is made: each REST request is answered by the first unused
+/// interaction with the same method, path and query, and the recorded websocket frames are fed
+/// to the client in order, each one waiting for the client messages that preceded it.
+#[derive(Clone, Debug)]
+pub struct Cassette {
+    state: Arc<Mutex<CassetteState>>,
+}
+
+#[derive(Debug)]
+struct CassetteState {
+    data: CassetteData,
+    mode: Mode,
+    path: PathBuf,
+    used: Vec<bool>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Mode {
+    Record,
+    Replay,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+struct CassetteData {
+    #[serde(default)]
+    http: Vec<Interaction>,
+    #[serde(default)]
+    websocket: Vec<Frame>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct RecordedRequest {
+    method: String,
+    path: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct RecordedResponse {
+    status: u16,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Frame {
+    Sent(String),
+    Received(String),
+}
+
+impl Cassette {
+    /// Creates an empty cassette that records to `path`, replacing any existing recording.
+    pub fn record<P>(path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self::new(
+            Mode::Record,
+            path.as_ref().to_path_buf(),
+            CassetteData::default(),
+        )
+    }
+
+    /// Loads the recording at `path` for replay.
+    pub fn replay<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let data = serde_json::from_slice(&fs::read(path.as_ref())?)?;
+        Ok(Self::new(Mode::Replay, path.as_ref().to_path_buf(), data))
+    }
+
+    fn new(mode: Mode, path: PathBuf, data: CassetteData) -> Self {
+        let used = vec![false; data.http.len()];
+        Self {
+            state: Arc::new(Mutex::new(CassetteState {
+                data,
+                mode,
+                path,
+                used,
+            })),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.state.lock().unwrap().mode == Mode::Record
+    }
+
+    /// Writes the recording to the cassette file. Does nothing in replay mode.
+    pub fn save(&self) -> io::Result<()> {
+        self.state.lock().unwrap().save()
+    }
+
+    /// A transport that records the traffic of the default [`ReqwestTransport`], or replays it.
+    pub fn transport(&self) -> CassetteTransport {
+        self.transport_with(ReqwestTransport::default())
+    }
+
+    /// A transport that records the traffic of `inner`, or replays it.
+    pub fn transport_with<T>(&self, inner: T) -> CassetteTransport
+    where
+        T: Transport,
+    {
+        CassetteTransport {
+            cassette: self.clone(),
+            inner: Arc::new(inner),
+        }
+    }
+
+    fn replay_http(&self, req: &RecordedRequest) -> Option<RecordedResponse> {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        let index = state
+            .data
+            .http
+            .iter()
+            .zip(state.used.iter())
+            .position(|(i, used)| !used && i.request == *req)?;
+
+        state.used[index] = true;
+        Some(state.data.http[index].response.clone())
+    }
+
+    fn record_http(&self, request: RecordedRequest, resp: &HttpResponse) {
+        let headers = resp
+            .headers
+            .iter()
+            .filter_map(|(k, v)| Some((k.to_string(), v.to_str().ok()?.to_string())))
+            .collect();
+        let response = RecordedResponse {
+            status: resp.status.as_u16(),
+            headers,
+            body: String::from_utf8_lossy(&resp.body).into_owned(),
+        };
+
+        let mut state = self.state.lock().unwrap();
+        state.data.http.push(Interaction { request, response });
+    }
+
+    fn record_frame(&self, frame: Frame) {
+        self.state.lock().unwrap().data.websocket.push(frame);
+    }
+
+    pub(crate) fn record_websocket(&self, sink: WSSink, source: WSSource) -> (WSSink, WSSource) {
+        let sink = {
+            let cassette = self.clone();
+            sink.with(move |msg: Message| {
+                if let Message::Text(t) = &msg {
+                    cassette.record_frame(Frame::Sent(t.clone()));
+                }
+                future::ready(Ok::<_, WsError>(msg))
+            })
+        };
+        let source = {
+            let cassette = self.clone();
+            source.inspect(move |msg| {
+                if let Ok(Message::Text(t)) = msg {
+                    cassette.record_frame(Frame::Received(t.clone()));
+                }
+            })
+        };
+
+        (Box::pin(sink), Box::pin(source))
+    }
+
+    pub(crate) fn replay_websocket(&self) -> (WSSink, WSSource) {
+        let frames = self.state.lock().unwrap().data.websocket.clone();
+        let (sent_tx, sent_rx) = mpsc::unbounded::<Message>();
+
+        let sink = sent_tx.sink_map_err(|_| WsError::ConnectionClosed);
+        let source = stream::unfold(
+            (frames.into_iter(), sent_rx),
+            |(mut frames, mut sent_rx)| async move {
+                loop {
+                    match frames.next()? {
+                        // Wait for the client to send the message that this frame was sent in
+                        // response to, e.g. a subscription request.
+                        Frame::Sent(_) => loop {
+                            if let Message::Text(_) = sent_rx.next().await? {
+                                break;
+                            }
+                        },
+                        Frame::Received(t) => {
+                            return Some((Ok(Message::Text(t)), (frames, sent_rx)))
+                        }
+                    }
+                }
+            },
+        );
+
+        (Box::pin(sink), Box::pin(source))
+    }
+}
+
+impl CassetteState {
+    fn save(&self) -> io::Result<()> {
+        if self.mode != Mode::Record {
+            return Ok(());
+        }
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_vec_pretty(&self.data)?)
+    }
+}
+
+impl Drop for CassetteState {
+    fn drop(&mut self) {
+        if let Err(e) = self.save() {
+            tracing::warn!(path = %self.path.display(), error = %e, "failed to save cassette");
+        }
+    }
+}
+
+/// A [`Transport`] that records to, or replays from, a [`Cassette`].
+#[derive(Clone, Debug)]
+pub struct CassetteTransport {
+    cassette: Cassette,
+    inner: Arc<dyn Transport>,
+}
+
+impl Transport for CassetteTransport {
+    fn send(&self, req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BoxError>> {
+        async move {
+            let recorded = RecordedRequest {
+                method: req.method.to_string(),
+                path: scrub_url(&req.url)?,
+            };
+
+            if self.cassette.is_recording() {
+                let resp = self.inner.send(req).await?;
+                self.cassette.record_http(recorded, &resp);
+                Ok(resp)
+            } else {
+                let resp = self.cassette.replay_http(&recorded).ok_or_else(|| {
+                    format!(
+                        "no recorded interaction for {} {}",
+                        recorded.method, recorded.path
+                    )
+                })?;
+
+                let mut headers = HeaderMap::new();
+                for (k, v) in &resp.headers {
+                    headers.insert(
+                        HeaderName::from_bytes(k.as_bytes())?,
+                        HeaderValue::from_str(v)?,
+                    );
+                }
+
+                Ok(HttpResponse {
+                    status: StatusCode::from_u16(resp.status)?,
+                    headers,
+                    body: resp.body.into_bytes(),
+                })
+            }
+        }
+        .boxed()
+    }
+}
+
+// Reduces a URL to its path and query, without the parameters that must not be recorded.
+fn scrub_url(url: &str) -> Result<String, BoxError> {
+    let url = url::Url::parse(url)?;
+    let query: String = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(
+            url.query_pairs()
+                .filter(|(k, _)| !k.is_empty() && !SCRUBBED_PARAMS.contains(&k.as_ref())),
+        )
+        .finish();
+
+    if query.is_empty() {
+        Ok(url.path().to_string())
+    } else {
+        Ok(format!("{}?{}", url.path(), query))
+    }
+}
+
+/// Replays the cassette `name` of `tests/cassettes`.
+#[cfg(test)]
+pub(crate) fn replay(name: &str) -> Cassette {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/cassettes")
+        .join(name);
+    Cassette::replay(path).unwrap()
+}
+
+/// A client answering its requests from the cassette `name` of `tests/cassettes`.
+#[cfg(test)]
+pub(crate) fn replay_client<A: Api>(name: &str) -> Client<A> {
+    Client::builder()
+        .transport(replay(name).transport())
+        .build()
+        .unwrap()
+}
+
+/// A market stream client replaying the frames of the cassette `name` of `tests/cassettes`.
+#[cfg(test)]
+pub(crate) async fn replay_ws_client<A: WSApi>(name: &str) -> (WSClient<A>, WSClientStream<A>) {
+    WSClient::builder()
+        .cassette(replay(name))
+        .market()
+        .await
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct StaticTransport;
+
+    impl Transport for StaticTransport {
+        fn send(&self, _: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BoxError>> {
+            let mut headers = HeaderMap::new();
+            headers.insert("x-mbx-used-weight-1m", HeaderValue::from_static("5"));
+            future::ready(Ok(HttpResponse {
+                status: StatusCode::OK,
+                headers,
+                body: br#"{"serverTime":1654041600000}"#.to_vec(),
+            }))
+            .boxed()
+        }
+    }
+
+    fn request(url: &str) -> HttpRequest {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-MBX-APIKEY", HeaderValue::from_static("secret-api-key"));
+        HttpRequest {
+            method: reqwest::Method::GET,
+            url: url.into(),
+            headers,
+        }
+    }
+
+    #[tokio::test]
+    async fn record_and_replay() {
+        let path = std::env::temp_dir().join(format!("binance-vcr-{}.json", std::process::id()));
+
+        let cassette = Cassette::record(&path);
+        let transport = cassette.transport_with(StaticTransport);
+        transport
+            .send(request(
+                "https://fapi.binance.com/fapi/v1/time?timestamp=1&signature=abc",
+            ))
+            .await
+            .unwrap();
+        drop((cassette, transport));
+
+        let recorded = fs::read_to_string(&path).unwrap();
+        assert!(!recorded.contains("secret-api-key"));
+        assert!(!recorded.contains("signature"));
+
+        let transport = Cassette::replay(&path).unwrap().transport();
+        fs::remove_file(&path).unwrap();
+
+        let resp = transport
+            .send(request(
+                "https://testnet.binancefuture.com/fapi/v1/time?timestamp=2&signature=def",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status, StatusCode::OK);
+        assert_eq!(resp.headers["x-mbx-used-weight-1m"], "5");
+        assert_eq!(resp.body, br#"{"serverTime":1654041600000}"#);
+
+        // Each interaction is only replayed once.
+        assert!(transport
+            .send(request("https://fapi.binance.com/fapi/v1/time"))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn scrub() {
+        assert_eq!(
+            scrub_url("https://fapi.binance.com/fapi/v1/order?symbol=BTCUSDT&orderId=1&recvWindow=5000&timestamp=1&signature=abc")
+                .unwrap(),
+            "/fapi/v1/order?symbol=BTCUSDT&orderId=1"
+        );
+        assert_eq!(
+            scrub_url("https://fapi.binance.com/fapi/v2/balance?&timestamp=1&signature=abc")
+                .unwrap(),
+            "/fapi/v2/balance"
+        );
+    }
+}
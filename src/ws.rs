@@ -5,31 +5,41 @@ use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
 use std::time::Duration;
 
-use async_tungstenite::{
-    tokio::{connect_async, ConnectStream},
-    tungstenite::Message,
-    WebSocketStream,
-};
+use async_tungstenite::tungstenite::{Error as WsError, Message};
 use futures::{
+    channel::{mpsc, oneshot},
     future::{self, Either, FutureExt},
-    sink::SinkExt,
-    stream::{SplitSink, SplitStream, Stream, StreamExt},
+    sink::{Sink, SinkExt},
+    stream::{Stream, StreamExt},
 };
 use serde::{de::DeserializeOwned, Serialize};
-use tokio::{
-    sync::{mpsc, oneshot},
-    time,
-};
 
-use crate::{error::*, extensions::*, models::*};
+use crate::{
+    client::{Environment, FuturesTestnet, SpotTestnet},
+    connection::ConnectionOptions,
+    error::*,
+    extensions::*,
+    models::*,
+    rt,
+    vcr::Cassette,
+};
 
 const WS_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 const WSSAPI_HOST: &str = "wss://stream.binance.com:9443/ws/";
+const WSSAPI_TESTNET_HOST: &str = "wss://testnet.binance.vision/ws/";
+const WSDAPI_HOST: &str = "wss://dstream.binance.com/ws/";
+const WSDAPI_TESTNET_HOST: &str = "wss://dstream.binancefuture.com/ws/";
 const WSFAPI_HOST: &str = "wss://fstream.binance.com/ws/";
+const WSFAPI_TESTNET_HOST: &str = "wss://stream.binancefuture.com/ws/";
 
+pub type WSDClient = WSClient<WSDApi>;
 pub type WSFClient = WSClient<WSFApi>;
 pub type WSSClient = WSClient<WSSApi>;
 
+pub(crate) type WSSink = Pin<Box<dyn Sink<Message, Error = WsError> + Send + Sync>>;
+pub(crate) type WSSource =
+    Pin<Box<dyn Stream<Item = std::result::Result<Message, WsError>> + Send>>;
+
 #[derive(Debug)]
 struct ClientState {
     is_closed: bool,
@@ -42,7 +52,7 @@ struct EventDispatcher<OrderType> {
     event_tx: mpsc::Sender<Result<WSEvent<OrderType>, WSApiCode>>,
     request_tx: mpsc::Sender<WSMessage<OrderType>>,
     state: Arc<Mutex<ClientState>>,
-    stream: SplitStream<WebSocketStream<ConnectStream>>,
+    stream: WSSource,
 }
 
 impl<OrderType> EventDispatcher<OrderType>
@@ -107,7 +117,7 @@ where
 struct RequestDispatcher<OrderType> {
     state: Arc<Mutex<ClientState>>,
     request_rx: mpsc::Receiver<WSMessage<OrderType>>,
-    sink: SplitSink<WebSocketStream<ConnectStream>, Message>,
+    sink: WSSink,
 }
 
 impl<OrderType> RequestDispatcher<OrderType>
@@ -115,15 +125,16 @@ where
     OrderType: DeserializeOwned,
 {
     async fn dispatch_requests(mut self) {
-        while let Some(msg) = self.request_rx.recv().await {
+        while let Some(msg) = self.request_rx.next().await {
             match msg {
                 WSMessage::Pong(p) => {
                     let _ = self.sink.send(Message::Pong(p)).await;
                 }
-                WSMessage::Request(req) => match self.dispatch_request(&req).await {
-                    Err(e) => self.return_error(e, req.id.as_ref().unwrap()).await,
-                    _ => (),
-                },
+                WSMessage::Request(req) => {
+                    if let Err(e) = self.dispatch_request(&req).await {
+                        self.return_error(e, req.id.as_ref().unwrap()).await;
+                    }
+                }
                 _ => (),
             }
         }
@@ -158,17 +169,35 @@ impl<A> WSClient<A>
 where
     A: WSApi,
 {
-    async fn connect<S>(stream: Option<WSStream<S>>) -> Result<(Self, WSClientStream<A>), WSApiCode>
+    pub fn builder() -> WSClientBuilder<A> {
+        WSClientBuilder::new()
+    }
+
+    async fn connect<S>(
+        host: &str,
+        stream: Option<WSStream<S>>,
+        connection: &ConnectionOptions,
+        cassette: Option<&Cassette>,
+    ) -> Result<(Self, WSClientStream<A>), WSApiCode>
     where
         S: AsRef<str>,
     {
         let path = match stream.as_ref() {
-            Some(s) => format!("{}{}", A::host(), s),
-            None => A::host().to_string(),
+            Some(s) => format!("{}{}", host, s),
+            None => host.to_string(),
         };
 
-        let (ws_stream, _) = connect_async(path).await?;
-        let (sink, stream) = ws_stream.split();
+        let (sink, stream) = match cassette {
+            Some(cassette) if !cassette.is_recording() => cassette.replay_websocket(),
+            _ => {
+                let ws_stream = connection.connect_websocket(&path).await?;
+                let (sink, stream) = ws_stream.split();
+                match cassette {
+                    Some(cassette) => cassette.record_websocket(Box::pin(sink), Box::pin(stream)),
+                    None => (Box::pin(sink) as WSSink, Box::pin(stream) as WSSource),
+                }
+            }
+        };
         let (event_tx, event_rx) = mpsc::channel(100);
         let (request_tx, request_rx) = mpsc::channel(1);
         let (close_tx, close_rx) = oneshot::channel();
@@ -185,7 +214,7 @@ where
                 request_rx,
                 sink,
             };
-            tokio::spawn(request_dispatcher.dispatch_requests());
+            rt::spawn(request_dispatcher.dispatch_requests());
         }
         {
             let request_tx = request_tx.clone();
@@ -197,7 +226,7 @@ where
                 state,
                 stream,
             };
-            tokio::spawn(event_dispatcher.dispatch_events());
+            rt::spawn(event_dispatcher.dispatch_events());
         }
 
         Ok((
@@ -211,6 +240,7 @@ where
         ))
     }
 
+    #[allow(clippy::result_large_err)]
     async fn send_request(&self, mut req: WSRequest) -> Result<WSResponse, WSApiCode> {
         let timeout = req.timeout;
         let (tx, rx) = oneshot::channel();
@@ -226,13 +256,13 @@ where
             state.next_id += 1;
         }
 
-        let _ = self.request_tx.send(WSMessage::Request(req)).await;
+        let _ = self.request_tx.clone().send(WSMessage::Request(req)).await;
 
         let wait_for_result =
             rx.map(|r| r.map_err(|_| Error::WebsocketRequestCancelled).x_flatten());
 
         let wait_for_timeout = match timeout {
-            Some(timeout) => Either::Left(time::sleep(timeout)),
+            Some(timeout) => Either::Left(rt::sleep(timeout)),
             None => Either::Right(future::pending::<()>()),
         }
         .map(|_| Err(Error::WebsocketRequestTimeout));
@@ -245,15 +275,14 @@ where
     }
 
     pub async fn market() -> Result<(Self, WSClientStream<A>), WSApiCode> {
-        let stream: Option<WSStream<&str>> = None;
-        Self::connect(stream).await
+        Self::builder().market().await
     }
 
     pub async fn user_data<S>(listen_key: S) -> Result<(Self, WSClientStream<A>), WSApiCode>
     where
         S: AsRef<str>,
     {
-        Self::connect(Some(WSStream::UserData(listen_key))).await
+        Self::builder().user_data(listen_key).await
     }
 
     pub fn close(self) {
@@ -322,6 +351,101 @@ where
     }
 }
 
+/// Builds a [`WSClient`], connecting to the production host of the API unless told otherwise.
+pub struct WSClientBuilder<A: WSApi> {
+    cassette: Option<Cassette>,
+    connection: ConnectionOptions,
+    host: Option<String>,
+    _marker: PhantomData<A>,
+}
+
+impl<A> WSClientBuilder<A>
+where
+    A: WSApi,
+{
+    pub fn new() -> Self {
+        Self {
+            cassette: None,
+            connection: ConnectionOptions::default(),
+            host: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets the connect timeout, proxy, TCP keepalive and TLS settings of the connection.
+    pub fn connection(mut self, connection: ConnectionOptions) -> Self {
+        self.connection = connection;
+        self
+    }
+
+    /// Records the frames of the connection to `cassette`, or replays them from it without
+    /// connecting.
+    pub fn cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Sets the base URL that stream names are appended to, e.g. `ws://127.0.0.1:9443/ws/`.
+    pub fn host<S>(mut self, host: S) -> Self
+    where
+        S: Into<String>,
+    {
+        let mut host = host.into();
+        if !host.ends_with('/') {
+            host.push('/');
+        }
+        self.host = Some(host);
+        self
+    }
+
+    pub fn environment<E>(self, env: E) -> Self
+    where
+        E: Environment<A>,
+    {
+        self.host(env.host())
+    }
+
+    fn resolve_host(&self) -> &str {
+        self.host.as_deref().unwrap_or_else(|| A::host())
+    }
+
+    pub async fn market(self) -> Result<(WSClient<A>, WSClientStream<A>), WSApiCode> {
+        let stream: Option<WSStream<&str>> = None;
+        WSClient::connect(
+            self.resolve_host(),
+            stream,
+            &self.connection,
+            self.cassette.as_ref(),
+        )
+        .await
+    }
+
+    pub async fn user_data<S>(
+        self,
+        listen_key: S,
+    ) -> Result<(WSClient<A>, WSClientStream<A>), WSApiCode>
+    where
+        S: AsRef<str>,
+    {
+        WSClient::connect(
+            self.resolve_host(),
+            Some(WSStream::UserData(listen_key)),
+            &self.connection,
+            self.cassette.as_ref(),
+        )
+        .await
+    }
+}
+
+impl<A> Default for WSClientBuilder<A>
+where
+    A: WSApi,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub struct WSClientStream<A: WSApi>(mpsc::Receiver<Result<WSEvent<A::OrderType>, WSApiCode>>);
 
 impl<A> Stream for WSClientStream<A>
@@ -331,7 +455,7 @@ where
     type Item = Result<WSEvent<A::OrderType>, WSApiCode>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.0.poll_recv(cx)
+        self.0.poll_next_unpin(cx)
     }
 }
 
@@ -341,6 +465,21 @@ pub trait WSApi: Send + Sync + Unpin + 'static {
     fn host() -> &'static str;
 }
 
+pub struct WSDApi;
+impl WSApi for WSDApi {
+    type OrderType = FOrderType;
+
+    fn host() -> &'static str {
+        WSDAPI_HOST
+    }
+}
+
+impl Environment<WSDApi> for FuturesTestnet {
+    fn host(&self) -> &str {
+        WSDAPI_TESTNET_HOST
+    }
+}
+
 pub struct WSFApi;
 impl WSApi for WSFApi {
     type OrderType = FOrderType;
@@ -350,6 +489,12 @@ impl WSApi for WSFApi {
     }
 }
 
+impl Environment<WSFApi> for FuturesTestnet {
+    fn host(&self) -> &str {
+        WSFAPI_TESTNET_HOST
+    }
+}
+
 pub struct WSSApi;
 impl WSApi for WSSApi {
     type OrderType = SOrderType;
@@ -359,58 +504,138 @@ impl WSApi for WSSApi {
     }
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-
-//     #[tokio::test]
-//     async fn test_wsclient() {
-//         let (client, stream) = WSFClient::market().await.unwrap();
-//         client
-//             .send_request(
-//                 WSRequest::new(WSRequestMethod::Subscribe).stream(WSStream::AggTrade("BTCUSDT")),
-//             )
-//             .await
-//             .unwrap();
-//         {
-//             tokio::spawn(async move {
-//                 time::sleep(time::Duration::from_secs(10)).await;
-//                 client.close();
-//             });
-//         }
-//         stream
-//             .for_each(|e| {
-//                 if let Ok(e) = e {
-//                     eprintln!("{:?}", e);
-//                 }
-//                 future::ready(())
-//             })
-//             .await;
-//     }
-
-//     #[tokio::test]
-//     async fn test_partial_depth() {
-//         let (client, stream) = WSFClient::market().await.unwrap();
-//         client
-//             .send_request(
-//                 WSRequest::new(WSRequestMethod::Subscribe)
-//                     .stream(WSStream::PartialBookDepth500ms("BTCUSDT", 20)),
-//             )
-//             .await
-//             .unwrap();
-//         {
-//             tokio::spawn(async move {
-//                 time::sleep(time::Duration::from_secs(10)).await;
-//                 client.close();
-//             });
-//         }
-//         stream
-//             .for_each(|e| {
-//                 if let Ok(e) = e {
-//                     eprintln!("{:?}", e);
-//                 }
-//                 future::ready(())
-//             })
-//             .await;
-//     }
-// }
+impl Environment<WSSApi> for SpotTestnet {
+    fn host(&self) -> &str {
+        WSSAPI_TESTNET_HOST
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+    use crate::vcr::replay_ws_client;
+
+    #[test]
+    fn host() {
+        let builder = WSFClient::builder();
+        assert_eq!(builder.resolve_host(), "wss://fstream.binance.com/ws/");
+
+        let builder = WSFClient::builder().host("ws://127.0.0.1:9443/ws");
+        assert_eq!(builder.resolve_host(), "ws://127.0.0.1:9443/ws/");
+    }
+
+    #[test]
+    fn environment() {
+        let builder = WSSClient::builder().environment(SpotTestnet);
+        assert_eq!(builder.resolve_host(), "wss://testnet.binance.vision/ws/");
+
+        let builder = WSFClient::builder().environment(FuturesTestnet);
+        assert_eq!(builder.resolve_host(), "wss://stream.binancefuture.com/ws/");
+
+        let builder = WSDClient::builder().environment(FuturesTestnet);
+        assert_eq!(
+            builder.resolve_host(),
+            "wss://dstream.binancefuture.com/ws/"
+        );
+    }
+
+    #[tokio::test]
+    async fn replay() {
+        replay_agg_trades().await;
+    }
+
+    #[cfg(feature = "runtime-async-std")]
+    #[test]
+    fn replay_async_std() {
+        async_std::task::block_on(replay_agg_trades());
+    }
+
+    async fn replay_agg_trades() {
+        let (client, stream) = replay_ws_client::<WSFApi>("fapi_ws_agg_trade.json").await;
+
+        client
+            .subscribe(WSStream::AggTrade("BTCUSDT"))
+            .await
+            .unwrap();
+
+        let events: Vec<_> = stream.map(|e| e.unwrap()).collect().await;
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].symbol(), Some("BTCUSDT"));
+        match &events[1].details {
+            WSEventDetails::AggTrade(trade) => assert_eq!(trade.id, 1200000002),
+            e => panic!("unexpected event {:?}", e),
+        }
+        assert!(client.is_closed());
+    }
+
+    #[tokio::test]
+    async fn replay_continuous_kline() {
+        let (client, stream) = replay_ws_client::<WSFApi>("fapi_ws_continuous_kline.json").await;
+
+        let kline =
+            WSStream::ContinuousKline("BTCUSDT", ContractType::Perpetual, ChartInterval::OneMinute);
+        assert_eq!(kline.to_string(), "btcusdt_perpetual@continuousKline_1m");
+        client.subscribe(kline).await.unwrap();
+
+        let events: Vec<_> = stream.map(|e| e.unwrap()).collect().await;
+        assert_eq!(events[0].symbol(), Some("BTCUSDT"));
+        match &events[0].details {
+            WSEventDetails::ContinuousKline {
+                contract_type,
+                details,
+                ..
+            } => {
+                assert_eq!(*contract_type, ContractType::Perpetual);
+                assert_eq!(details.num_trades, 2390);
+            }
+            e => panic!("unexpected event {:?}", e),
+        }
+    }
+
+    #[tokio::test]
+    async fn replay_partial_depth() {
+        let (client, stream) = replay_ws_client::<WSFApi>("fapi_ws_partial_depth.json").await;
+
+        client
+            .subscribe(WSStream::PartialBookDepth500ms("BTCUSDT", 5))
+            .await
+            .unwrap();
+
+        let events: Vec<_> = stream.map(|e| e.unwrap()).collect().await;
+        assert_eq!(events[0].symbol(), Some("BTCUSDT"));
+        match &events[0].details {
+            WSEventDetails::OrderBookUpdate(depth) => {
+                assert_eq!(depth.last_id, 1590000000042);
+                assert_eq!(depth.bids.len(), 2);
+                assert_eq!(depth.asks[0].0, "31780.2".parse().unwrap());
+            }
+            e => panic!("unexpected event {:?}", e),
+        }
+    }
+
+    #[tokio::test]
+    async fn replay_tickers() {
+        let (client, stream) = replay_ws_client::<WSFApi>("fapi_ws_ticker.json").await;
+
+        client
+            .subscribe(WSStream::BookTicker("BTCUSDT"))
+            .await
+            .unwrap();
+        client.subscribe(WSStream::Ticker("BTCUSDT")).await.unwrap();
+
+        let mut events: Vec<_> = stream.map(|e| e.unwrap()).collect().await;
+        let ticker = Ticker24hr::try_from(events.pop().unwrap()).unwrap();
+        assert_eq!(ticker.symbol, "BTCUSDT");
+        assert_eq!(ticker.first_id, 2300011034);
+        assert_eq!(ticker.num_trades, 2572698);
+
+        let event = events.pop().unwrap();
+        let event = Ticker24hr::try_from(event).unwrap_err();
+        let book_ticker = BookTicker::try_from(event).unwrap();
+        assert_eq!(book_ticker.symbol, "BTCUSDT");
+        assert_eq!(book_ticker.update_id, Some(1590000000123));
+        assert_eq!(book_ticker.best_ask_qty, "2.005".parse().unwrap());
+    }
+}